@@ -50,7 +50,7 @@ impl Command for DeployCommand {
     }
 
     fn get_params_help(&self) -> &'static str {
-        "<sender> <file_path> <module_name = Contract>"
+        "<sender> <file_path> [config_path]"
     }
 
     fn get_description(&self) -> &'static str {
@@ -59,7 +59,7 @@ impl Command for DeployCommand {
 
     #[allow(clippy::needless_return)]
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 3 {
+        if params.len() != 3 && params.len() != 4 {
             println!("Invalid number of arguments");
             println!("Usage: {} {}", params[0], self.get_params_help());
             return;
@@ -68,6 +68,7 @@ impl Command for DeployCommand {
         let sender = params[1];
         let source = params[2];
         let source_path = PathBuf::from(source);
+        let config_path = params.get(3).map(PathBuf::from);
 
         // Compile sprint program
         println!("Compiling sprint program...");
@@ -78,6 +79,8 @@ impl Command for DeployCommand {
             output_path: None,
             verbose: false,
             check: false,
+            search_paths: Vec::new(),
+            config_path,
         };
         match sprintc::compile(&args) {
             Ok(path) => {
@@ -4,6 +4,7 @@ use client::client_proxy::ClientProxy;
 use sprint_move::script::Deposit;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 use tempfile::NamedTempFile;
 
 pub struct DepositCommand {}
@@ -14,7 +15,7 @@ impl Command for DepositCommand {
     }
 
     fn get_params_help(&self) -> &'static str {
-        "<author> <module_name> <depositer> <amount>"
+        "<author> <module_name> <depositer> <amount> <currency> <file_path>"
     }
 
     fn get_description(&self) -> &'static str {
@@ -23,12 +24,34 @@ impl Command for DepositCommand {
 
     #[allow(clippy::needless_return)]
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 5 {
+        if params.len() != 7 {
             println!("Invalid number of arguments");
             println!("Usage: {} {}", params[0], self.get_params_help());
             return;
         }
 
+        // Currencies are deposited to balances by their positions among those of the contract.
+        let currencies = match sprintc::currencies(Path::new(params[6]), &[]) {
+            Ok(currencies) => currencies,
+            Err(e) => {
+                println!("Failed to read the currencies of {}... {}", params[6], e);
+                return;
+            }
+        };
+
+        let currency = match currencies.iter().position(|currency| currency == params[5]) {
+            Some(currency) => currency as u64,
+            None => {
+                println!(
+                    "Unknown currency {}, expected one of {}",
+                    params[5],
+                    currencies.join(", ")
+                );
+                println!("Usage: {} {}", params[0], self.get_params_help());
+                return;
+            }
+        };
+
         println!("Generating transaction code...");
         // TODO: Add proper error handling if any of these are invalid.
         let author = hex::encode(
@@ -43,6 +66,7 @@ impl Command for DepositCommand {
             module: params[2].into(),
             amount: params[4].into(),
             coin_store_index: 0, // TODO: Unhard code this
+            currency,
         };
 
         // Create a file inside of `std::env::temp_dir()`.
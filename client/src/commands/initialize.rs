@@ -4,6 +4,7 @@ use client::client_proxy::ClientProxy;
 use sprint_move::script::InitializeContract;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 use tempfile::NamedTempFile;
 
 pub struct InitializeCommand {}
@@ -14,21 +15,44 @@ impl Command for InitializeCommand {
     }
 
     fn get_params_help(&self) -> &'static str {
-        "<author> <module_name> <party> <counterparty>"
+        "<author> <module_name> <party> <counterparty> [<role>...] [--config <config_path>] \
+         [-- <argument>...]"
     }
 
     fn get_description(&self) -> &'static str {
-        "Initalize a new instance of a deployed contract between two parties."
+        "Initalize a new instance of a deployed contract between its roles."
     }
 
     #[allow(clippy::needless_return)]
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 5 {
+        // The arguments of `main` follow the addresses of the roles, after `--`.
+        let (params, arguments) = match params.iter().position(|param| *param == "--") {
+            Some(separator) => (&params[..separator], &params[separator + 1..]),
+            None => (params, &[][..]),
+        };
+
+        // Decimal arguments are given at the precision of the configuration the contract was
+        // compiled with, if any.
+        let (params, config_path) = match params {
+            [params @ .., "--config", path] => (params, Some(Path::new(*path))),
+            _ => (params, None),
+        };
+
+        if params.len() < 5 {
             println!("Invalid number of arguments");
             println!("Usage: {} {}", params[0], self.get_params_help());
             return;
         }
 
+        let arguments = match sprintc::arguments(arguments, config_path) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                println!("{}", e);
+                println!("Usage: {} {}", params[0], self.get_params_help());
+                return;
+            }
+        };
+
         // TODO: Allow for client to chose the address which executes the transaction
         let sender = "0";
 
@@ -40,24 +64,23 @@ impl Command for InitializeCommand {
                 .unwrap()
                 .to_vec(),
         );
-        let party = hex::encode(
-            client
-                .get_account_address_from_parameter(params[3])
-                .unwrap()
-                .to_vec(),
-        );
-        let counterparty = hex::encode(
-            client
-                .get_account_address_from_parameter(params[4])
-                .unwrap()
-                .to_vec(),
-        );
+        let roles = params[3..]
+            .iter()
+            .map(|role| {
+                let address = client
+                    .get_account_address_from_parameter(role)
+                    .unwrap()
+                    .to_vec();
+
+                format!("0x{}", hex::encode(address))
+            })
+            .collect();
 
         let initialize_contract = InitializeContract {
             author: format!("0x{}", author),
             module: params[2].into(),
-            party: format!("0x{}", party),
-            counterparty: format!("0x{}", counterparty),
+            roles,
+            arguments,
         };
 
         // Create a file inside of `std::env::temp_dir()`.
@@ -0,0 +1,272 @@
+use sprint_parser::ast;
+use std::str::FromStr;
+
+/// How quotients are rounded, when multiplying or dividing decimals, scaling contracts and paying
+/// the amounts they are scaled to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rounding {
+    /// Towards zero.
+    Down,
+    /// Away from zero.
+    Up,
+    /// To the nearest, with halves rounded up.
+    Nearest,
+    /// To the nearest, with halves rounded to the even neighbour.
+    Even,
+}
+
+impl Rounding {
+    /// Divides words, rounding the quotient.
+    pub fn divide(self, numerator: u64, denominator: u64) -> u64 {
+        self.round(
+            numerator / denominator,
+            numerator % denominator,
+            denominator,
+        )
+        .expect("a rounded quotient is at most its numerator")
+    }
+
+    /// Rounds a quotient given the remainder of its division, unless rounding it up overflows.
+    pub fn round(self, quotient: u64, remainder: u64, denominator: u64) -> Option<u64> {
+        let half = denominator - remainder;
+
+        let up = match self {
+            Self::Down => false,
+            Self::Up => remainder > 0,
+            Self::Nearest => remainder >= half,
+            Self::Even => remainder > half || (remainder == half && quotient % 2 == 1),
+        };
+
+        quotient.checked_add(up as u64)
+    }
+
+    /// The condition under which the generated `round` rounds up, in terms of its `quotient`,
+    /// `remainder` and `denominator`, mirroring `round`.
+    pub fn condition(self) -> &'static str {
+        match self {
+            Self::Down => "false",
+            Self::Up => "copy(remainder) > 0",
+            Self::Nearest => "copy(remainder) >= copy(denominator) - copy(remainder)",
+            Self::Even => {
+                "copy(remainder) > copy(denominator) - copy(remainder) || \
+                 (copy(remainder) == copy(denominator) - copy(remainder) && \
+                 copy(quotient) % 2 == 1)"
+            }
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "down" => Ok(Self::Down),
+            "up" => Ok(Self::Up),
+            "nearest" => Ok(Self::Nearest),
+            "even" => Ok(Self::Even),
+            _ => Err(format!(
+                "Unknown rounding \"{}\", expected \"down\", \"up\", \"nearest\" or \"even\"",
+                s
+            )),
+        }
+    }
+}
+
+/// The representation of decimals as words, which count units of `10^-precision`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FixedPoint {
+    pub precision: u32,
+    pub rounding: Rounding,
+}
+
+impl FixedPoint {
+    /// The word representing one, unless the precision is too great for it to fit.
+    pub fn unit(&self) -> Option<u64> {
+        10u64.checked_pow(self.precision)
+    }
+
+    /// Multiplies decimals as the generated `multiply` does, unless the product overflows. The
+    /// whole units of the left operand are multiplied separately from the rest, so that the
+    /// product only overflows if the result does.
+    pub fn multiply(&self, left: u64, right: u64) -> Option<u64> {
+        let unit = self.unit()?;
+        let part = (left % unit).checked_mul(right)?;
+        let quotient = (left / unit).checked_mul(right)?.checked_add(part / unit)?;
+        self.rounding.round(quotient, part % unit, unit)
+    }
+
+    /// Divides decimals as the generated `ratio` does, unless the quotient overflows or the
+    /// denominator is zero. Likewise, the numerator is divided before it is multiplied by one,
+    /// and the remainder is divided without overflowing, however large the denominator.
+    pub fn ratio(&self, numerator: u64, denominator: u64) -> Option<u64> {
+        let unit = self.unit()?;
+        // The remainder is less than the denominator, so both parts of its division fit a word.
+        let part = u128::from(numerator.checked_rem(denominator)?) * u128::from(unit);
+        let quotient = (numerator / denominator)
+            .checked_mul(unit)?
+            .checked_add((part / u128::from(denominator)) as u64)?;
+        self.rounding.round(
+            quotient,
+            (part % u128::from(denominator)) as u64,
+            denominator,
+        )
+    }
+
+    /// The word representing a decimal literal, rounded to the precision, unless it is too large.
+    pub fn literal(&self, decimal: &ast::Decimal) -> Option<u64> {
+        if decimal.places <= self.precision {
+            10u64
+                .checked_pow(self.precision - decimal.places)
+                .and_then(|factor| decimal.digits.checked_mul(factor))
+        } else {
+            // Digits of more than 19 places past the precision are less than half of a unit.
+            let divisor = 10u64.checked_pow(decimal.places - self.precision);
+            Some(divisor.map_or(
+                u64::from(decimal.digits > 0 && self.rounding == Rounding::Up),
+                |divisor| self.rounding.divide(decimal.digits, divisor),
+            ))
+        }
+    }
+}
+
+impl Default for FixedPoint {
+    fn default() -> Self {
+        Self {
+            precision: 6,
+            rounding: Rounding::Down,
+        }
+    }
+}
+
+/// Checks that one and each decimal literal in the definitions can be represented.
+pub(crate) fn check(
+    definitions: &[ast::Definition],
+    fixed_point: &FixedPoint,
+) -> Result<(), String> {
+    fn find(expression: &ast::Expression, found: &mut Vec<ast::Decimal>) {
+        if let ast::ExpressionType::Decimal(decimal) = &expression.expression {
+            found.push(*decimal);
+        }
+
+        for child in expression.children() {
+            find(child, found);
+        }
+    }
+
+    if fixed_point.unit().is_none() {
+        return Err(format!(
+            "Decimals cannot be represented with a precision of {} places",
+            fixed_point.precision
+        ));
+    }
+
+    let mut found = Vec::new();
+
+    for definition in definitions {
+        find(&definition.expression, &mut found);
+    }
+
+    match found
+        .into_iter()
+        .find(|decimal| fixed_point.literal(decimal).is_none())
+    {
+        Some(decimal) => Err(format!(
+            "The decimal {} is too large to be represented with a precision of {} places",
+            decimal, fixed_point.precision
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divide() {
+        let quotients = [
+            (Rounding::Down, [2, 2, 2, 3]),
+            (Rounding::Up, [3, 3, 3, 3]),
+            (Rounding::Nearest, [2, 3, 3, 3]),
+            (Rounding::Even, [2, 2, 3, 3]),
+        ];
+
+        for (rounding, expected) in quotients.iter() {
+            let actual: Vec<_> = [9, 10, 11, 12]
+                .iter()
+                .map(|numerator| rounding.divide(*numerator, 4))
+                .collect();
+
+            assert_eq!(actual, expected);
+        }
+
+        assert_eq!(Rounding::Even.divide(14, 4), 4);
+    }
+
+    #[test]
+    fn multiply() {
+        let fixed_point = FixedPoint {
+            precision: 1,
+            rounding: Rounding::Even,
+        };
+
+        // 1.5 * 0.5 is 0.75, rounded to 0.8, and 3.5 * 0.5 is 1.75, also rounded to 1.8
+        assert_eq!(fixed_point.multiply(15, 5), Some(8));
+        assert_eq!(fixed_point.multiply(35, 5), Some(18));
+        // 2.5 * 0.3 is 0.75 too, but 2.5 * 0.1 is 0.25, rounded to 0.2
+        assert_eq!(fixed_point.multiply(25, 3), Some(8));
+        assert_eq!(fixed_point.multiply(25, 1), Some(2));
+
+        // Products only overflow when the result does.
+        assert_eq!(fixed_point.multiply(u64::MAX - 5, 10), Some(u64::MAX - 5));
+        assert_eq!(fixed_point.multiply(u64::MAX - 5, 11), None);
+        assert_eq!(
+            FixedPoint::default().multiply(20_000_000_000_000, 1_000_000),
+            Some(20_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn ratio() {
+        let fixed_point = FixedPoint {
+            precision: 1,
+            rounding: Rounding::Nearest,
+        };
+
+        // 1.0 / 0.3 is 3.33.., rounded to 3.3, and 2.0 / 0.3 is 6.66.., rounded to 6.7
+        assert_eq!(fixed_point.ratio(10, 3), Some(33));
+        assert_eq!(fixed_point.ratio(20, 3), Some(67));
+        assert_eq!(fixed_point.ratio(10, 0), None);
+
+        // Quotients only overflow when the result does.
+        assert_eq!(
+            FixedPoint::default().ratio(20_000_000_000_000, 2_000_000),
+            Some(10_000_000_000_000)
+        );
+        assert_eq!(FixedPoint::default().ratio(20_000_000_000_000, 1), None);
+
+        // Nor when the remainder times one does, for denominators above the largest word over one.
+        assert_eq!(
+            FixedPoint::default().ratio(30_000_000_000_000, 20_000_000_000_000),
+            Some(1_500_000)
+        );
+        assert_eq!(fixed_point.ratio(u64::MAX - 1, u64::MAX), Some(10));
+        assert_eq!(fixed_point.ratio(u64::MAX / 20, u64::MAX), Some(0));
+    }
+
+    #[test]
+    fn literal() {
+        let fixed_point = FixedPoint {
+            precision: 2,
+            rounding: Rounding::Nearest,
+        };
+        let decimal = |digits, places| ast::Decimal { digits, places };
+
+        assert_eq!(fixed_point.literal(&decimal(25, 1)), Some(250));
+        assert_eq!(fixed_point.literal(&decimal(125, 3)), Some(13));
+        assert_eq!(fixed_point.literal(&decimal(1, 25)), Some(0));
+        assert_eq!(fixed_point.literal(&decimal(u64::MAX, 1)), None);
+        assert_eq!(decimal(25, 3).to_string(), "0.025");
+    }
+}
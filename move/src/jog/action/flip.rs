@@ -1,5 +1,10 @@
 use super::{
-    super::{expression::Address, identifier::Identifier, kind::Kind, variable::Variable},
+    super::{
+        expression::{Address, Expression},
+        identifier::Identifier,
+        kind::Kind,
+        variable::Variable,
+    },
     Action,
 };
 use std::fmt::{self, Display, Formatter};
@@ -36,3 +41,44 @@ impl Display for Flip {
         )
     }
 }
+
+/// Makes the address of the role at a position the party, and the party the counterparty. Roles
+/// keep the addresses the contract was initialized with, whoever holds the contract since.
+#[derive(Debug)]
+pub struct GiveTo<'a> {
+    role: Expression<'a>,
+}
+
+impl<'a> GiveTo<'a> {
+    pub fn new(role: Expression<'a>) -> Self {
+        GiveTo { role }
+    }
+}
+
+impl Action for GiveTo<'_> {
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["0x0.Vector"]
+    }
+
+    fn definitions(&self) -> Vec<&Variable> {
+        vec![&FLIP_STORE]
+    }
+}
+
+impl Display for GiveTo<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "{} = *(&{});", FLIP_STORE.identifier(), Address::Party)?;
+        writeln!(
+            f,
+            "*(&mut {}) = Vector.get<address>(&copy(context_ref).roles, {});",
+            Address::Party,
+            self.role,
+        )?;
+        write!(
+            f,
+            "*(&mut {}) = move({});",
+            Address::Counterparty,
+            FLIP_STORE.identifier()
+        )
+    }
+}
@@ -0,0 +1,36 @@
+use super::{
+    super::{expression::Expression, variable::Variable},
+    Action,
+};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug)]
+pub struct Guard<'a> {
+    observable: Expression<'a>,
+}
+
+impl<'a> Guard<'a> {
+    pub fn new(observable: Expression<'a>) -> Self {
+        Self { observable }
+    }
+}
+
+impl Action for Guard<'_> {
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn definitions(&self) -> Vec<&Variable> {
+        vec![]
+    }
+}
+
+impl Display for Guard<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Vector.push_back<u64>(&mut copy(context_ref).guards, {});",
+            self.observable
+        )
+    }
+}
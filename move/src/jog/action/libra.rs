@@ -1,6 +1,6 @@
 use super::{
     super::{
-        expression::{Address, Expression},
+        expression::Expression,
         variable::{Variable, EVENT},
     },
     Action,
@@ -45,18 +45,20 @@ impl Display for Deposit {
     }
 }
 
+/// Pays the scale of a context in a currency to its payee, which is its party unless it has been
+/// routed to an address.
 #[derive(Debug)]
-pub struct Withdraw {
-    payee: Address,
+pub struct Withdraw<'a> {
+    currency: Expression<'a>,
 }
 
-impl Withdraw {
-    pub fn new(payee: Address) -> Self {
-        Withdraw { payee }
+impl<'a> Withdraw<'a> {
+    pub fn new(currency: Expression<'a>) -> Self {
+        Withdraw { currency }
     }
 }
 
-impl Action for Withdraw {
+impl Action for Withdraw<'_> {
     fn dependencies(&self) -> &'static [&'static str] {
         DEPENDENCIES
     }
@@ -66,21 +68,21 @@ impl Action for Withdraw {
     }
 }
 
-impl Display for Withdraw {
+impl Display for Withdraw<'_> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
             "LibraAccount.deposit(
-                *(&{}),
+                Self.payee(freeze(copy(context_ref))),
                 LibraCoin.withdraw(
                     Vector.borrow_mut<LibraCoin.T>(
                         &mut copy(contract_ref).coinstores,
-                        *(&copy(context_ref).coinstore_index),
+                        *(&copy(context_ref).coinstore_index) + {},
                     ),
-                    *(&mut copy(context_ref).scale)
+                    Self.amount(*(&copy(context_ref).scale))
                 )
             );",
-            self.payee,
+            self.currency,
         )
     }
 }
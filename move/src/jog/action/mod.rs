@@ -3,7 +3,9 @@ pub mod assign;
 pub mod call;
 pub mod drop;
 pub mod flip;
+pub mod guard;
 pub mod libra;
+pub mod pay_to;
 pub mod push;
 pub mod scale;
 pub mod spawn;
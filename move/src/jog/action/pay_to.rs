@@ -0,0 +1,32 @@
+use super::{
+    super::{expression::Expression, variable::Variable},
+    Action,
+};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug)]
+pub struct PayTo<'a> {
+    payee: Expression<'a>,
+}
+
+impl<'a> PayTo<'a> {
+    pub fn new(payee: Expression<'a>) -> Self {
+        PayTo { payee }
+    }
+}
+
+impl Action for PayTo<'_> {
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn definitions(&self) -> Vec<&Variable> {
+        vec![]
+    }
+}
+
+impl Display for PayTo<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "*(&mut copy(context_ref).payee) = {};", self.payee)
+    }
+}
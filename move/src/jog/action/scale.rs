@@ -29,7 +29,7 @@ impl Display for Scale<'_> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
-            "*(&mut copy(context_ref).scale) = *(&copy(context_ref).scale) * {};",
+            "*(&mut copy(context_ref).scale) = Self.multiply(*(&copy(context_ref).scale), {});",
             self.scalar
         )
     }
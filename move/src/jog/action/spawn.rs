@@ -39,8 +39,12 @@ impl Display for Spawn<'_> {
                 coinstore_index: *(&copy(context_ref).coinstore_index),
                 party: *(&{}),
                 counterparty: *(&{}),
+                roles: Self.clone_roles(&copy(context_ref).roles),
+                payee: *(&copy(context_ref).payee),
                 scale: *(&copy(context_ref).scale),
                 stack: Self.clone_stack(freeze(copy({}))),
+                guards: Self.clone_stack(&copy(context_ref).guards),
+                observations: Self.clone_stack(&copy(context_ref).observations),
             }};",
             self.context.identifier(),
             self.root,
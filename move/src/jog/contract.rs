@@ -1,16 +1,63 @@
-use super::method::Method;
+use super::{
+    call::Call, expression::Expression, identifier::Identifier, kind::Kind, method::Method,
+    variable::Variable,
+};
+use crate::fixed_point::FixedPoint;
 use askama::Template;
-use std::collections::HashSet;
+use sprint_parser::ast::Duration;
+use std::collections::{BTreeSet, HashSet};
 
 const DEPENDENCIES: [&str; 3] = ["0x0.Vector", "0x0.LibraCoin", "0x0.LibraAccount"];
 
+/// Observables are passed as their position among those of the contract plus this many times the
+/// length of the stack they were passed with.
+pub const OBSERVABLE_FRAME: u64 = 1 << 32;
+
 #[derive(Template, Default)]
 #[template(path = "contract.mvir", escape = "none")]
 pub struct Contract<'a> {
     initial_state: u64,
     stack_offset: u64,
     methods: Vec<Method<'a>>,
+    horizons: Vec<(u64, Expression<'a>)>,
+    observables: Vec<Expression<'a>>,
+    observations: Vec<Observation<'a>>,
     dependencies: Vec<&'a str>,
+    fixed_point: FixedPoint,
+    currencies: Vec<&'a str>,
+    addresses: Vec<String>,
+    roles: Vec<Identifier<'a>>,
+    arguments: Vec<(Variable<'a>, Expression<'a>)>,
+}
+
+/// The observations of an observable on a schedule, which are recorded by `observe_<n>` at
+/// positions of the observations of a context from `offset`.
+pub struct Observation<'a> {
+    offset: u64,
+    start: u64,
+    period: u64,
+    count: u64,
+    now: Expression<'a>,
+    value: Expression<'a>,
+}
+
+impl<'a> Observation<'a> {
+    pub fn new(
+        start: u64,
+        period: u64,
+        count: u64,
+        now: Expression<'a>,
+        value: Expression<'a>,
+    ) -> Self {
+        Self {
+            offset: 0,
+            start,
+            period,
+            count,
+            now,
+            value,
+        }
+    }
 }
 
 impl<'a> Contract<'a> {
@@ -31,6 +78,114 @@ impl<'a> Contract<'a> {
         self.methods.push(method);
     }
 
+    pub fn add_horizon(&mut self, state: u64, horizon: Expression<'a>) {
+        self.horizons.push((state, horizon));
+    }
+
+    /// Adds the code of an observable evaluated by `observe`, returning its position.
+    pub fn add_observable(&mut self, observable: Expression<'a>) -> u64 {
+        self.observables.push(observable);
+        self.observables.len() as u64 - 1
+    }
+
+    pub fn frame(&self) -> u64 {
+        OBSERVABLE_FRAME
+    }
+
+    pub fn month(&self) -> u64 {
+        Duration::MONTH
+    }
+
+    /// Adds the observations of an observable, returning the position of the first one.
+    pub fn add_observation(&mut self, mut observation: Observation<'a>) -> u64 {
+        let offset = self.observation_count();
+        observation.offset = offset;
+        self.observations.push(observation);
+        offset
+    }
+
+    pub fn observation_count(&self) -> u64 {
+        self.observations
+            .iter()
+            .map(|observation| observation.count)
+            .sum()
+    }
+
+    pub fn set_fixed_point(&mut self, fixed_point: FixedPoint) {
+        self.fixed_point = fixed_point;
+    }
+
+    /// The word representing one, which is checked to fit before any code is generated.
+    pub fn unit(&self) -> u64 {
+        self.fixed_point.unit().unwrap()
+    }
+
+    /// The number of decimal places of decimals.
+    pub fn precision(&self) -> u32 {
+        self.fixed_point.precision
+    }
+
+    /// Sets the currencies paid by the contract, each of which has a balance in each context.
+    pub fn set_currencies(&mut self, currencies: Vec<&'a str>) {
+        self.currencies = currencies;
+    }
+
+    /// The position of the balance of a currency among those of a context.
+    pub fn currency(&self, name: &str) -> u64 {
+        self.currencies
+            .iter()
+            .position(|currency| *currency == name)
+            .unwrap() as u64
+    }
+
+    /// Sets the addresses the contract can route payments to, however each of them is written.
+    pub fn set_addresses(&mut self, addresses: Vec<&str>) {
+        let addresses: BTreeSet<_> = addresses.into_iter().map(canonical).collect();
+        self.addresses = addresses.into_iter().collect();
+    }
+
+    /// The payee of a context routed to an address, which is its position among the addresses of
+    /// the contract from 1, as a payee of 0 is the party of the context.
+    pub fn address(&self, address: &str) -> u64 {
+        let address = canonical(address);
+
+        self.addresses.iter().position(|a| *a == address).unwrap() as u64 + 1
+    }
+
+    /// Sets the roles of the contract in the order they are declared, each of which is bound to
+    /// an address when it is initialized.
+    pub fn set_roles(&mut self, roles: Vec<&'a str>) {
+        self.roles = roles.into_iter().map(Identifier::Prefixed).collect();
+    }
+
+    /// Sets the arguments of `main`, which the contract is initialized with after the addresses of
+    /// its roles, along with the words they are pushed onto the stack as.
+    pub fn set_arguments(&mut self, arguments: Vec<Variable<'a>>) {
+        self.arguments = arguments
+            .into_iter()
+            .map(|argument| {
+                // Arguments are named apart from the roles, which they may share names with.
+                let identifier = match argument.identifier() {
+                    Identifier::Prefixed(name) => Identifier::Argument(name),
+                    identifier => identifier.clone(),
+                };
+                let argument = Variable::new(identifier, argument.kind().clone());
+                let value = Expression::Identifier(argument.identifier().clone()).r#move();
+
+                let value = match argument.kind() {
+                    Kind::Boolean => {
+                        let mut call = Call::from(Identifier::Raw("encode_bool"));
+                        call.add_argument(value);
+                        call.into()
+                    }
+                    _ => value,
+                };
+
+                (argument, value)
+            })
+            .collect();
+    }
+
     pub fn set_initial_state(&mut self, state: u64) {
         self.initial_state = state;
     }
@@ -39,3 +194,13 @@ impl<'a> Contract<'a> {
         self.stack_offset = offset;
     }
 }
+
+// Addresses are compared by their value, so `0x2A` and `0x002a` are the same address.
+fn canonical(address: &str) -> String {
+    let digits = address[2..].trim_start_matches('0').to_lowercase();
+
+    match digits.is_empty() {
+        true => String::from("0x0"),
+        false => format!("0x{}", digits),
+    }
+}
@@ -1,5 +1,5 @@
 use super::{call::Call, identifier::Identifier, kind::Kind};
-use crate::numbers::Numbers;
+use crate::{numbers::Numbers, oracle::Oracle};
 use std::{
     borrow::Cow,
     cell::RefCell,
@@ -20,8 +20,9 @@ pub enum Expression<'a> {
     Length(Kind, Box<Self>),
     Move(Box<Self>),
     MutableReference(Box<Self>),
+    Not(Box<Self>),
     Numbers(Rc<RefCell<Numbers>>),
-    Observable(&'a str),
+    Observable(&'a Oracle),
     Reference(Box<Self>),
     State(Rc<RefCell<Option<u64>>>),
     Unsigned(u64),
@@ -46,8 +47,9 @@ impl Display for Expression<'_> {
             Self::Length(k, v) => write!(f, "Vector.length<{}>({})", k, v),
             Self::Move(e) => write!(f, "move({})", e),
             Self::MutableReference(e) => write!(f, "&mut {}", e),
+            Self::Not(e) => write!(f, "!{}", e),
             Self::Numbers(n) => n.borrow().peek().fmt(f),
-            Self::Observable(o) => write!(f, "{}.get_value({{{{alice}}}})", o),
+            Self::Observable(o) => write!(f, "{}.get_value({})", o.name(), o.address),
             Self::Reference(e) => write!(f, "&{}", e),
             Self::State(u) => u.borrow().unwrap().fmt(f),
             Self::Unsigned(u) => u.fmt(f),
@@ -122,10 +124,16 @@ impl<'a> TryFrom<Expression<'a>> for u64 {
 #[derive(Clone, Debug)]
 pub enum Binary {
     Add,
+    And,
+    Divide,
+    Equal,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
+    Multiply,
+    NotEqual,
+    Or,
     Subtract,
 }
 
@@ -133,10 +141,16 @@ impl Display for Binary {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Binary::Add => write!(f, "+"),
+            Binary::And => write!(f, "&&"),
+            Binary::Divide => write!(f, "/"),
+            Binary::Equal => write!(f, "=="),
             Binary::Greater => write!(f, ">"),
             Binary::GreaterEqual => write!(f, ">="),
             Binary::Less => write!(f, "<"),
             Binary::LessEqual => write!(f, "<="),
+            Binary::Multiply => write!(f, "*"),
+            Binary::NotEqual => write!(f, "!="),
+            Binary::Or => write!(f, "||"),
             Binary::Subtract => write!(f, "-"),
         }
     }
@@ -2,9 +2,13 @@ use std::fmt::{self, Display, Formatter};
 
 const PREFIX: &str = "s";
 const SPAWN: &str = "spawn";
+const ARGUMENT: &str = "argument";
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Identifier<'a> {
+    /// An argument of `main`, named apart from the roles the contract is initialized with.
+    Argument(&'a str),
+    Branch(&'a str, u64),
     Raw(&'a str),
     Prefixed(&'a str),
     Spawn(u64),
@@ -14,7 +18,10 @@ pub enum Identifier<'a> {
 impl Identifier<'_> {
     pub fn has_name(&self, needle: &str) -> bool {
         match &self {
-            Self::Raw(name) | Self::Prefixed(name) | Self::Transition(name) => *name == needle,
+            Self::Argument(name)
+            | Self::Raw(name)
+            | Self::Prefixed(name)
+            | Self::Transition(name) => *name == needle,
             _ => false,
         }
     }
@@ -23,6 +30,8 @@ impl Identifier<'_> {
 impl Display for Identifier<'_> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Self::Argument(name) => write!(f, "{}_{}", ARGUMENT, name),
+            Self::Branch(name, index) => write!(f, "transition_{}_{}", name, index),
             Self::Raw(name) => name.fmt(f),
             Self::Prefixed(name) => write!(f, "{}_{}", PREFIX, name),
             Self::Spawn(id) => write!(f, "{}_{}", SPAWN, id),
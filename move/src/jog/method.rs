@@ -26,7 +26,7 @@ pub struct Method<'a> {
     actions: Vec<Box<dyn Action + 'a>>,
     post_actions: Vec<Box<dyn Action + 'a>>,
     emit_actions: Vec<Box<dyn Action + 'a>>,
-    result: Option<Expression<'a>>,
+    result: Option<(Kind, Expression<'a>)>,
     acquires_resource: bool,
 }
 
@@ -52,8 +52,8 @@ impl<'a> Method<'a> {
         Self::new(true, identifier)
     }
 
-    pub fn transition(name: &'a str, from: u64, to: Expression<'a>) -> Self {
-        let mut method = Self::public(Identifier::Transition(name));
+    pub fn transition(identifier: Identifier<'a>, from: u64, to: Expression<'a>) -> Self {
+        let mut method = Self::public(identifier);
 
         method.add_action(Assign::new(
             CONTRACT_REF.clone(),
@@ -80,6 +80,13 @@ impl<'a> Method<'a> {
             ),
         ));
 
+        method.add_action(Assert::new(
+            Expression::Expression(
+                format!("!Self.abandoned(copy({}))", CONTEXT_REF.identifier()).into(),
+            ),
+            2,
+        ));
+
         method.add_action(Assign::new(
             STACK.clone(),
             Expression::Expression("&mut copy(context_ref).stack".into()),
@@ -154,8 +161,8 @@ impl<'a> Method<'a> {
         self.post_actions.push(Box::new(post_action));
     }
 
-    pub fn set_result(&mut self, expression: Expression<'a>) {
-        self.result = Some(expression);
+    pub fn set_result(&mut self, kind: Kind, expression: Expression<'a>) {
+        self.result = Some((kind, expression));
     }
 
     pub fn set_acquires_resource(&mut self) {
@@ -165,7 +172,14 @@ impl<'a> Method<'a> {
     fn result(&self) -> String {
         self.result
             .as_ref()
-            .map(|e| format!(" {}", e))
+            .map(|(_, e)| format!(" {}", e))
+            .unwrap_or_default()
+    }
+
+    fn result_kind(&self) -> String {
+        self.result
+            .as_ref()
+            .map(|(k, _)| format!(": {}", k))
             .unwrap_or_default()
     }
 
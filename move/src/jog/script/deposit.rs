@@ -7,4 +7,5 @@ pub struct Deposit {
     pub module: String,
     pub amount: String,
     pub coin_store_index: u64,
+    pub currency: u64,
 }
@@ -5,6 +5,10 @@ use askama::Template;
 pub struct InitializeContract {
     pub author: String,
     pub module: String,
-    pub party: String,
-    pub counterparty: String,
+    /// The addresses of the roles of the contract in the order they are declared, starting with
+    /// the party and counterparty.
+    pub roles: Vec<String>,
+    /// The arguments of `main`, as words or booleans, with decimals written as the words which
+    /// represent them and dates as seconds since the epoch.
+    pub arguments: Vec<String>,
 }
@@ -1,11 +1,42 @@
+mod fixed_point;
 mod jog;
 mod numbers;
+mod oracle;
 mod visitor;
 
-use self::visitor::definitions;
+use self::visitor::{definitions, inline, observation, scale};
+pub use fixed_point::{FixedPoint, Rounding};
 pub use jog::script;
+pub use oracle::{Oracle, Oracles, DATE};
 use sprint_parser::ast;
+pub use visitor::definitions::currencies;
 
-pub fn generate(definitions: &[ast::Definition]) -> String {
-    definitions::visit(definitions).to_string()
+/// The configuration of the generated module.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// The oracles providing the observables used, by their names.
+    pub oracles: Oracles,
+    /// The representation of decimals.
+    pub fixed_point: FixedPoint,
+}
+
+/// Generates a module for the definitions, which calls the oracles configured for the observables
+/// they use.
+pub fn generate(definitions: &[ast::Definition], config: &Config) -> Result<String, String> {
+    if let Some(name) = oracle::observables(definitions)
+        .into_iter()
+        .find(|name| !config.oracles.contains_key(name))
+    {
+        return Err(format!(
+            "No oracle is configured for the observable \"{}\"",
+            name
+        ));
+    }
+
+    inline::check(definitions)?;
+    observation::check(definitions)?;
+    fixed_point::check(definitions, &config.fixed_point)?;
+    scale::check(definitions, &config.fixed_point)?;
+
+    Ok(definitions::visit(definitions, &config.oracles, config.fixed_point).to_string())
 }
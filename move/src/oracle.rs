@@ -0,0 +1,70 @@
+use sprint_parser::ast;
+use std::collections::HashMap;
+
+/// The name of the observable giving the current date, on which primitives such as `before` and
+/// `after` depend.
+pub const DATE: &str = "Date";
+
+/// The oracles providing the values of observables, by the names of the observables.
+pub type Oracles = HashMap<String, Oracle>;
+
+/// A module providing the value of an observable through `get_value(owner: address): u64`, such
+/// as `tests/observables/oracle.mvir`.
+#[derive(Clone, Debug)]
+pub struct Oracle {
+    /// The module, qualified by the address it is published at, e.g. `0x0.Date`.
+    pub module: String,
+    /// The account storing the value of the observable.
+    pub address: String,
+}
+
+impl Oracle {
+    pub fn new(module: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            module: module.into(),
+            address: address.into(),
+        }
+    }
+
+    /// The name of the module, without the address it is published at.
+    pub fn name(&self) -> &str {
+        self.module.rsplit('.').next().unwrap()
+    }
+}
+
+/// The name of the observable giving the rate at which a currency exchanges for another, e.g.
+/// `USD/EUR`.
+pub(crate) fn exchange(from: &str, to: &str) -> String {
+    format!("{}/{}", from, to)
+}
+
+/// The names of the observables used by the definitions, each of which needs an oracle.
+pub(crate) fn observables(definitions: &[ast::Definition]) -> Vec<String> {
+    fn find(expression: &ast::Expression, found: &mut Vec<String>) {
+        match &expression.expression {
+            ast::ExpressionType::Date(ast::Date::Now)
+            | ast::ExpressionType::Observable(ast::Observable::Observed(_, _, _)) => {
+                found.push(DATE.to_string())
+            }
+            ast::ExpressionType::Observable(ast::Observable::Named(name)) => {
+                found.push(name.to_string())
+            }
+            ast::ExpressionType::Observable(ast::Observable::Exchange(from, to)) => {
+                found.push(exchange(from, to))
+            }
+            _ => {}
+        }
+
+        for child in expression.children() {
+            find(child, found);
+        }
+    }
+
+    let mut found = Vec::new();
+
+    for definition in definitions {
+        find(&definition.expression, &mut found);
+    }
+
+    found
+}
@@ -1,7 +1,9 @@
 use super::{expression, Context};
 use crate::jog::{
     action::push::Push,
+    call::Call,
     expression::{Binary, Expression},
+    identifier::Identifier,
     kind::Kind,
     variable::STACK,
 };
@@ -11,11 +13,21 @@ pub(super) fn visit<'a>(
     context: &mut Context<'a, '_>,
     expression: &ast::Expression<'a>,
 ) -> Vec<Push<'a>> {
+    if expression::is_observable(expression.kind()) {
+        let value = expression::visit_passed_observable(context, expression);
+        return vec![Push::new(STACK.clone(), value)];
+    }
+
     if !expression::results_in_state(expression.kind()) {
-        return vec![Push::new(
-            STACK.clone(),
-            expression::visit(context, expression),
-        )];
+        let mut value = expression::visit(context, expression);
+
+        if expression::visit_kind(expression.kind()) == Kind::Boolean {
+            let mut call = Call::from(Identifier::Raw("encode_bool"));
+            call.add_argument(value);
+            value = call.into();
+        }
+
+        return vec![Push::new(STACK.clone(), value)];
     }
 
     match &expression.expression {
@@ -1,26 +1,43 @@
-use super::{expression, Context, FunctionContext};
-use crate::jog::{
-    contract::Contract, identifier::Identifier, kind::Kind, method::Method, variable::Variable,
+use super::{expression, inline, Context, FunctionContext};
+use crate::{
+    fixed_point::FixedPoint,
+    jog::{contract::Contract, identifier::Identifier, method::Method, variable::Variable},
+    oracle::Oracles,
 };
 use sprint_parser::ast;
-use std::{convert::TryInto, rc::Rc};
+use std::{collections::BTreeSet, convert::TryInto, rc::Rc};
 
 pub(super) const TERMINAL_ID: u64 = 0;
 
-pub fn visit<'a>(definitions: &[ast::Definition<'a>]) -> Contract<'a> {
-    let definitions = definitions.iter().map(Rc::new);
-    let mut context = Context::new(definitions.clone());
+pub fn visit<'a>(
+    definitions: &[ast::Definition<'a>],
+    oracles: &'a Oracles,
+    fixed_point: FixedPoint,
+) -> Contract<'a> {
+    let definitions = inline::visit(definitions);
+    let mut context = Context::new(definitions.iter().map(Rc::new), oracles, fixed_point);
+    context.contract.set_fixed_point(fixed_point);
+    context.contract.set_currencies(currencies(&definitions));
+    context.contract.set_addresses(addresses(&definitions));
+    context.contract.set_roles(roles(&definitions));
 
-    for definition in definitions {
+    for definition in definitions.iter() {
         let mut expression = &definition.expression;
         let mut arguments = Vec::new();
 
         while let ast::ExpressionType::Abstraction(a, e) = &expression.expression {
             expression = &e;
-            arguments.push(Variable::new(Identifier::Prefixed(a.name), Kind::Unsigned));
+            arguments.push(Variable::new(
+                Identifier::Prefixed(a.name),
+                expression::visit_kind(a.kind.clone()),
+            ));
         }
 
         if expression::results_in_state(expression.kind()) {
+            if definition.variable.name == "main" {
+                context.contract.set_arguments(arguments.clone());
+            }
+
             context
                 .function_context
                 .replace(FunctionContext::new(definition.variable.name, arguments));
@@ -40,10 +57,16 @@ pub fn visit<'a>(definitions: &[ast::Definition<'a>]) -> Contract<'a> {
                 context.contract.set_initial_state(state);
             }
         } else {
+            // Arguments of private methods are passed directly rather than on the stack.
+            context.function_context.take();
+
             let mut method = Method::private(Identifier::Prefixed(definition.variable.name));
 
             method.set_arguments(arguments);
-            method.set_result(expression::visit(&mut context, expression));
+            method.set_result(
+                expression::visit_kind(expression.kind()),
+                expression::visit(&mut context, expression),
+            );
             context.contract.add_method(method);
         }
     }
@@ -53,3 +76,65 @@ pub fn visit<'a>(definitions: &[ast::Definition<'a>]) -> Contract<'a> {
         .set_stack_offset(context.numbers.borrow().peek());
     context.contract
 }
+
+/// The currencies paid by the definitions, which are numbered in the order of their names, so that
+/// the balances they are deposited to are known from the currencies used alone.
+pub fn currencies<'a>(definitions: &[ast::Definition<'a>]) -> Vec<&'a str> {
+    fn find<'a>(expression: &ast::Expression<'a>, found: &mut BTreeSet<&'a str>) {
+        if let ast::ExpressionType::Currency(name) = &expression.expression {
+            found.insert(name);
+        }
+
+        for child in expression.children() {
+            find(child, found);
+        }
+    }
+
+    let mut found = BTreeSet::new();
+
+    for definition in definitions {
+        find(&definition.expression, &mut found);
+    }
+
+    found.into_iter().collect()
+}
+
+// Addresses are collected wherever they are used, and numbered by the contract once deduplicated.
+fn addresses<'a>(definitions: &[ast::Definition<'a>]) -> Vec<&'a str> {
+    fn find<'a>(expression: &ast::Expression<'a>, found: &mut Vec<&'a str>) {
+        if let ast::ExpressionType::Address(address) = &expression.expression {
+            found.push(address);
+        }
+
+        for child in expression.children() {
+            find(child, found);
+        }
+    }
+
+    let mut found = Vec::new();
+
+    for definition in definitions {
+        find(&definition.expression, &mut found);
+    }
+
+    found
+}
+
+// Roles are defined as themselves, so they are found among the definitions whether or not they
+// are used. Programs which declare none have a party and a counterparty.
+fn roles<'a>(definitions: &[ast::Definition<'a>]) -> Vec<&'a str> {
+    let mut roles: Vec<_> = definitions
+        .iter()
+        .filter_map(|definition| match definition.expression.expression {
+            ast::ExpressionType::Role(index, name) => Some((index, name)),
+            _ => None,
+        })
+        .collect();
+
+    if roles.is_empty() {
+        return vec!["party", "counterparty"];
+    }
+
+    roles.sort();
+    roles.into_iter().map(|(_, name)| name).collect()
+}
@@ -1,14 +1,17 @@
 use super::{argument, state, Context};
-use crate::jog::{
-    action::push::Push,
-    call::Call,
-    expression::{Address, Binary, Expression},
-    identifier::Identifier,
-    kind::Kind,
-    method::Method,
-    variable::{STACK, STACK_LENGTH},
+use crate::{
+    jog::{
+        action::push::Push,
+        call::Call,
+        contract::Observation,
+        expression::{Binary, Expression},
+        identifier::Identifier,
+        kind::Kind,
+        method::Method,
+        variable::{CONTEXT_REF, STACK, STACK_LENGTH},
+    },
+    oracle::{exchange, DATE},
 };
-use chrono::NaiveDate;
 use sprint_parser::ast;
 use std::{cell::RefCell, rc::Rc};
 
@@ -18,11 +21,26 @@ pub(super) fn visit<'a>(
 ) -> Expression<'a> {
     match &expression.expression {
         ast::ExpressionType::Abstraction(_, _) => unreachable!("use visit_abstraction instead"),
+        ast::ExpressionType::Address(address) => {
+            Expression::Unsigned(context.contract.address(address))
+        }
+        ast::ExpressionType::Amount(_, e) => visit(context, e),
         ast::ExpressionType::Application(f, a) => visit_application(context, &f, &a),
-        ast::ExpressionType::Boolean(_) => unimplemented!(),
+        ast::ExpressionType::Boolean(b) => Expression::Expression(b.to_string().into()),
         ast::ExpressionType::Class(c) => visit_class(context, &c),
+        ast::ExpressionType::Currency(name) => {
+            Expression::Unsigned(context.contract.currency(name))
+        }
         ast::ExpressionType::Date(d) => visit_date(context, &d),
+        ast::ExpressionType::Decimal(d) => Expression::Unsigned(
+            context
+                .fixed_point
+                .literal(d)
+                .expect("decimals are checked to fit before any code is generated"),
+        ),
+        ast::ExpressionType::Duration(duration) => Expression::Unsigned(duration.word().unwrap()),
         ast::ExpressionType::Observable(o) => visit_observable(context, &o),
+        ast::ExpressionType::Role(index, _) => Expression::Unsigned(*index),
         ast::ExpressionType::State(s) => visit_state(context, &s),
         ast::ExpressionType::Variable(v) => visit_variable(context, &v, Vec::new()),
         ast::ExpressionType::Word(w) => Expression::Expression(w.to_string().into()),
@@ -86,25 +104,164 @@ fn visit_class<'a>(context: &mut Context<'a, '_>, class: &ast::Class<'a>) -> Exp
                 visit(context, right).into(),
             )
         }
-        ast::Class::Equatable(_) => unimplemented!(),
-        ast::Class::Negatable(_) => unimplemented!(),
-        ast::Class::Numerable(_) => unimplemented!(),
+        ast::Class::Equatable(e) => {
+            let (binary, left, right) = match e {
+                ast::Equatable::Equal(left, right) => (Binary::Equal, left, right),
+                ast::Equatable::NotEqual(left, right) => (Binary::NotEqual, left, right),
+            };
+
+            Expression::Binary(
+                binary,
+                visit(context, left).into(),
+                visit(context, right).into(),
+            )
+        }
+        ast::Class::Boolean(ast::Boolean::Not(e)) => Expression::Not(visit(context, e).into()),
+        ast::Class::Boolean(b) => {
+            let (binary, left, right) = match b {
+                ast::Boolean::And(left, right) => (Binary::And, left, right),
+                ast::Boolean::Or(left, right) => (Binary::Or, left, right),
+                ast::Boolean::Not(_) => unreachable!(),
+            };
+
+            Expression::Binary(
+                binary,
+                visit(context, left).into(),
+                visit(context, right).into(),
+            )
+        }
+        // Words are unsigned, so negation aborts unless the operand is zero.
+        ast::Class::Negatable(ast::Negatable::Negate(e)) => Expression::Binary(
+            Binary::Subtract,
+            Expression::Unsigned(0).into(),
+            visit(context, e).into(),
+        ),
+        // Products are checked for overflow, and those of decimals rounded to their precision, as
+        // are their quotients.
+        ast::Class::Numerable(ast::Numerable::Multiply(left, right, _)) => {
+            let name = match is_decimal(class.operand_kind()) {
+                true => "multiply",
+                false => "product",
+            };
+
+            let mut call = Call::from(Identifier::Raw(name));
+            call.add_argument(visit(context, left));
+            call.add_argument(visit(context, right));
+            call.into()
+        }
+        ast::Class::Numerable(ast::Numerable::Divide(left, right))
+            if is_decimal(class.operand_kind()) =>
+        {
+            let mut call = Call::from(Identifier::Raw("ratio"));
+            call.add_argument(visit(context, left));
+            call.add_argument(visit(context, right));
+            call.into()
+        }
+        // Dates are offset by calendar months, so durations are added to and subtracted from
+        // them by `later` and `earlier`, unless both are literals.
+        ast::Class::Numerable(ast::Numerable::Add(left, right, kind)) if is_date(kind.clone()) => {
+            visit_offset(context, left, right, true)
+        }
+        ast::Class::Numerable(ast::Numerable::Subtract(left, right, kind))
+            if is_date(kind.clone()) =>
+        {
+            visit_offset(context, left, right, false)
+        }
+        ast::Class::Numerable(n) => {
+            let (binary, left, right) = match n {
+                ast::Numerable::Add(left, right, _) => (Binary::Add, left, right),
+                ast::Numerable::Subtract(left, right, _) => (Binary::Subtract, left, right),
+                ast::Numerable::Multiply(_, _, _) => unreachable!(),
+                ast::Numerable::Divide(left, right) => (Binary::Divide, left, right),
+            };
+
+            Expression::Binary(
+                binary,
+                visit(context, left).into(),
+                visit(context, right).into(),
+            )
+        }
     }
 }
 
-fn visit_date<'a>(context: &mut Context<'a, '_>, date: &ast::Date) -> Expression<'a> {
+fn visit_date<'a>(context: &mut Context<'a, '_>, date: &ast::Date<'a>) -> Expression<'a> {
     match date {
-        ast::Date::Now => {
-            context.contract.add_dependency("{{alice}}.Date");
-            Expression::Observable("Date")
+        ast::Date::Now => visit_oracle(context, DATE),
+        ast::Date::Date(timestamp) => (*timestamp).into(),
+        ast::Date::Horizon(contract) => {
+            let index = match &contract.expression {
+                ast::ExpressionType::Variable(variable) => context
+                    .function_context
+                    .as_ref()
+                    .and_then(|function_context| function_context.find_argument(variable.name)),
+                _ => None,
+            }
+            .expect("Horizons can only be taken of arguments");
+
+            let mut call = Call::from(Identifier::Raw("horizon"));
+            call.add_argument(Expression::Identifier(STACK.identifier().clone()).copy());
+            call.add_argument(argument_index(index));
+            call.into()
+        }
+        ast::Date::Earliest(dates) => {
+            let dates = dates.iter().map(|date| visit(context, date)).collect();
+            fold("minimum", dates).expect("The earliest of no dates")
         }
-        ast::Date::Date(year, month, day, hour, minute, second) => {
-            let timestamp = NaiveDate::from_ymd(*year as i32, *month as u32, *day as u32)
-                .and_hms(*hour as u32, *minute as u32, *second as u32)
-                .timestamp() as u64;
-            timestamp.into()
+        ast::Date::Latest(dates) => {
+            let dates = dates.iter().map(|date| visit(context, date)).collect();
+            fold("maximum", dates).expect("The latest of no dates")
+        }
+    }
+}
+
+/// Combines several words pairwise with a function of the contract, e.g. into the maximum of
+/// several dates.
+pub(super) fn fold<'a>(
+    function: &'static str,
+    words: Vec<Expression<'a>>,
+) -> Option<Expression<'a>> {
+    words.into_iter().fold(None, |folded, word| {
+        Some(match folded {
+            None => word,
+            Some(folded) => {
+                let mut call = Call::from(Identifier::Raw(function));
+                call.add_argument(folded);
+                call.add_argument(word);
+                call.into()
+            }
+        })
+    })
+}
+
+fn visit_offset<'a>(
+    context: &mut Context<'a, '_>,
+    date: &ast::Expression<'a>,
+    duration: &ast::Expression<'a>,
+    later: bool,
+) -> Expression<'a> {
+    if let (
+        ast::ExpressionType::Date(ast::Date::Date(timestamp)),
+        ast::ExpressionType::Duration(d),
+    ) = (&date.expression, &duration.expression)
+    {
+        let offset = match later {
+            true => d.after(*timestamp),
+            false => d.before(*timestamp),
+        };
+
+        // Dates out of range are left to abort when they are computed.
+        if let Some(offset) = offset {
+            return offset.into();
         }
     }
+
+    let mut call = Call::from(Identifier::Raw(match later {
+        true => "later",
+        false => "earlier",
+    }));
+    call.add_argument(visit(context, date));
+    call.add_argument(visit(context, duration));
+    call.into()
 }
 
 fn visit_observable<'a>(
@@ -112,20 +269,175 @@ fn visit_observable<'a>(
     observable: &ast::Observable<'a>,
 ) -> Expression<'a> {
     match observable {
-        ast::Observable::IsParty => {
-            Expression::Expression(format!("get_txn_sender() == *(&{})", Address::Party).into())
-        }
+        ast::Observable::IsParty => Expression::Expression(
+            format!("get_txn_sender() == *({})", field(context, "party")).into(),
+        ),
         ast::Observable::IsCounterparty => Expression::Expression(
-            format!("get_txn_sender() == *(&{})", Address::Counterparty).into(),
+            format!("get_txn_sender() == *({})", field(context, "counterparty")).into(),
+        ),
+        ast::Observable::IsRole(role) => Expression::Expression(
+            format!(
+                "get_txn_sender() == Vector.get<address>({}, {})",
+                field(context, "roles"),
+                visit(context, role)
+            )
+            .into(),
         ),
         ast::Observable::Konst(e) => visit(context, e),
+        ast::Observable::Fixed(e) if is_decimal(e.kind()) => visit(context, e),
+        ast::Observable::Fixed(e) => product(
+            visit(context, e),
+            Expression::Unsigned(context.fixed_point.unit().unwrap()),
+        ),
+        ast::Observable::Named(name) => visit_oracle(context, name),
+        ast::Observable::Exchange(from, to) => visit_oracle(context, &exchange(from, to)),
+        ast::Observable::Lift(_, _) => unreachable!("lifted functions are applied when inlined"),
+        ast::Observable::Max(_, _) | ast::Observable::Min(_, _) => {
+            let (name, left, right) = match observable {
+                ast::Observable::Max(left, right) => ("maximum", left, right),
+                ast::Observable::Min(left, right) => ("minimum", left, right),
+                _ => unreachable!(),
+            };
+
+            let mut call = Call::from(Identifier::Raw(name));
+            call.add_argument(visit(context, left));
+            call.add_argument(visit(context, right));
+            call.into()
+        }
+        ast::Observable::Observed(statistic, schedule, e) => {
+            // Observations are recorded by `observe_<n>`, which is given the context.
+            let observing = std::mem::replace(&mut context.observing, false);
+            let now = visit_oracle(context, DATE);
+            let value = visit(context, e);
+            context.observing = observing;
+
+            let offset = context.contract.add_observation(Observation::new(
+                schedule.start,
+                schedule.period.word().unwrap(),
+                schedule.count,
+                now,
+                value,
+            ));
+
+            let mut call = Call::from(Identifier::Raw(match statistic {
+                ast::Statistic::Average => "observed_average",
+                ast::Statistic::Maximum => "observed_maximum",
+                ast::Statistic::Minimum => "observed_minimum",
+            }));
+            call.add_argument(field(context, "observations"));
+            call.add_argument(Expression::Unsigned(offset));
+            call.add_argument(Expression::Unsigned(schedule.count));
+            call.add_argument(Expression::Binary(
+                Binary::GreaterEqual,
+                visit_oracle(context, DATE).into(),
+                Expression::Unsigned(schedule.end()).into(),
+            ));
+            call.into()
+        }
+    }
+}
+
+// The fields of the context given to `observe`, which are borrowed from the context outside of it.
+const OBSERVED_FIELDS: [&str; 4] = ["party", "counterparty", "roles", "observations"];
+
+fn field<'a>(context: &Context<'a, '_>, name: &str) -> Expression<'a> {
+    match context.observing {
+        true => Expression::Expression(format!("copy({})", name).into()),
+        false => {
+            Expression::Expression(format!("&copy({}).{}", CONTEXT_REF.identifier(), name).into())
+        }
+    }
+}
+
+/// The word an observable is passed on the stack as, from which `observe` evaluates it whenever
+/// it is used. Observables which are arguments are passed on as they are.
+pub(super) fn visit_passed_observable<'a>(
+    context: &mut Context<'a, '_>,
+    observable: &ast::Expression<'a>,
+) -> Expression<'a> {
+    if let ast::ExpressionType::Variable(variable) = &observable.expression {
+        if let Some(i) = context
+            .function_context
+            .as_ref()
+            .and_then(|function_context| function_context.find_argument(variable.name))
+        {
+            return stack_argument(i);
+        }
+    }
+
+    let observing = std::mem::replace(&mut context.observing, true);
+    let mut value = visit(context, observable);
+    context.observing = observing;
+
+    if visit_kind(observable.kind()) == Kind::Boolean {
+        let mut call = Call::from(Identifier::Raw("encode_bool"));
+        call.add_argument(value);
+        value = call.into();
     }
+
+    let index = context.contract.add_observable(value);
+
+    Expression::Binary(
+        Binary::Add,
+        Expression::Unsigned(index).into(),
+        Expression::Binary(
+            Binary::Multiply,
+            Expression::Identifier(STACK_LENGTH.identifier().clone())
+                .copy()
+                .into(),
+            Expression::Unsigned(context.contract.frame()).into(),
+        )
+        .into(),
+    )
+}
+
+// Evaluates an observable passed on the stack.
+fn observe<'a>(context: &Context<'a, '_>, observable: Expression<'a>) -> Expression<'a> {
+    let mut call = Call::from(Identifier::Raw("observe"));
+    call.add_argument(Expression::Identifier(STACK.identifier().clone()).copy());
+
+    for name in OBSERVED_FIELDS.iter() {
+        call.add_argument(field(context, name));
+    }
+
+    call.add_argument(observable);
+    call.into()
+}
+
+// Oracles are checked to be configured for every observable before any code is generated.
+fn visit_oracle<'a>(context: &mut Context<'a, '_>, name: &str) -> Expression<'a> {
+    let oracle = &context.oracles[name];
+    context.contract.add_dependency(&oracle.module);
+    Expression::Observable(oracle)
 }
 
 fn visit_state<'a>(context: &mut Context<'a, '_>, state: &ast::state::State<'a>) -> Expression<'a> {
     Expression::Unsigned(state::visit(context, state))
 }
 
+/// The word on the stack of the argument `i`.
+pub(super) fn stack_argument<'a>(i: u64) -> Expression<'a> {
+    Expression::Get(
+        Kind::Unsigned,
+        Expression::Identifier(STACK.identifier().clone())
+            .copy()
+            .freeze()
+            .into(),
+        argument_index(i).into(),
+    )
+}
+
+/// The position on the stack of the argument `i`, counted from the last one.
+pub(super) fn argument_index<'a>(i: u64) -> Expression<'a> {
+    Expression::Binary(
+        Binary::Subtract,
+        Expression::Identifier(STACK_LENGTH.identifier().clone())
+            .copy()
+            .into(),
+        Expression::Unsigned(i + 1).into(),
+    )
+}
+
 fn visit_variable<'a>(
     context: &mut Context<'a, '_>,
     variable: &ast::Variable<'a>,
@@ -135,25 +447,24 @@ fn visit_variable<'a>(
         None => match context
             .function_context
             .as_ref()
-            .unwrap()
-            .find_argument(variable.name)
+            .and_then(|function_context| function_context.find_argument(variable.name))
         {
             None => Expression::Identifier(Identifier::Prefixed(variable.name)).copy(),
-            Some(i) => Expression::Get(
-                Kind::Unsigned,
-                Expression::Identifier(STACK.identifier().clone())
-                    .copy()
-                    .freeze()
-                    .into(),
-                Expression::Binary(
-                    Binary::Subtract,
-                    Expression::Identifier(STACK_LENGTH.identifier().clone())
-                        .copy()
-                        .into(),
-                    Expression::Unsigned(i + 1).into(),
-                )
-                .into(),
-            ),
+            Some(i) => {
+                let argument = match is_observable(variable.kind.clone()) {
+                    true => observe(context, stack_argument(i)),
+                    false => stack_argument(i),
+                };
+
+                match visit_kind(variable.kind.clone()) {
+                    Kind::Boolean => Expression::Binary(
+                        Binary::NotEqual,
+                        argument.into(),
+                        Expression::Unsigned(0).into(),
+                    ),
+                    _ => argument,
+                }
+            }
         },
         Some(definition) => {
             let arguments = arguments.into_iter().rev();
@@ -164,7 +475,8 @@ fn visit_variable<'a>(
                 let to = visit_abstraction(context, &definition.expression);
 
                 let function_context = context.function_context.as_ref().unwrap();
-                let mut method = Method::transition(function_context.name, from, to);
+                let mut method =
+                    Method::transition(Identifier::Transition(function_context.name), from, to);
 
                 // We need to get context.numbers out before we visit arguments, since until
                 // stacks is consumed we can't borrow context immutably.
@@ -213,20 +525,88 @@ fn visit_variable<'a>(
             } else {
                 let mut call = Call::from(Identifier::Prefixed(variable.name));
 
-                for argument in arguments.map(|argument| visit(context, argument)) {
-                    call.add_argument(argument);
+                // Polymorphic definitions take and return words, so booleans are encoded as such
+                // when they are used at a boolean kind.
+                let mut kind = definition.variable.kind.clone();
+                let mut used_kind = variable.kind.clone();
+
+                for argument in arguments {
+                    let mut value = visit(context, argument);
+
+                    if let (ast::Kind::Abstraction(from, to), ast::Kind::Abstraction(_, used_to)) = (
+                        ast::Kind::simplify(kind.clone()).as_ref(),
+                        ast::Kind::simplify(used_kind.clone()).as_ref(),
+                    ) {
+                        if visit_kind(from.clone()) == Kind::Unsigned
+                            && visit_kind(argument.kind()) == Kind::Boolean
+                        {
+                            let mut encode = Call::from(Identifier::Raw("encode_bool"));
+                            encode.add_argument(value);
+                            value = encode.into();
+                        }
+
+                        kind = to.clone();
+                        used_kind = used_to.clone();
+                    }
+
+                    call.add_argument(value);
                 }
 
-                call.into()
+                match (visit_kind(kind), visit_kind(used_kind)) {
+                    (Kind::Unsigned, Kind::Boolean) => Expression::Binary(
+                        Binary::NotEqual,
+                        Expression::from(call).into(),
+                        Expression::Unsigned(0).into(),
+                    ),
+                    _ => call.into(),
+                }
             }
         }
     }
 }
 
+pub(super) fn visit_kind(kind: Rc<ast::Kind>) -> Kind {
+    match ast::Kind::simplify(kind).as_ref() {
+        ast::Kind::Boolean => Kind::Boolean,
+        ast::Kind::Observable(k) => visit_kind(k.clone()),
+        _ => Kind::Unsigned,
+    }
+}
+
+pub(super) fn is_observable(kind: Rc<ast::Kind>) -> bool {
+    matches!(ast::Kind::simplify(kind).as_ref(), ast::Kind::Observable(_))
+}
+
+fn product<'a>(left: Expression<'a>, right: Expression<'a>) -> Expression<'a> {
+    let mut call = Call::from(Identifier::Raw("product"));
+    call.add_argument(left);
+    call.add_argument(right);
+    call.into()
+}
+
+// Whether a kind is of decimals, or observable decimals, rather than words. Amounts and exchange
+// rates are decimals.
+pub(super) fn is_decimal(kind: Rc<ast::Kind>) -> bool {
+    match ast::Kind::simplify(kind).as_ref() {
+        ast::Kind::Amount(_) | ast::Kind::Decimal | ast::Kind::Rate(_, _) => true,
+        ast::Kind::Observable(k) => is_decimal(k.clone()),
+        _ => false,
+    }
+}
+
+// Whether a kind is of dates, or observable dates.
+fn is_date(kind: Rc<ast::Kind>) -> bool {
+    match ast::Kind::simplify(kind).as_ref() {
+        ast::Kind::Date => true,
+        ast::Kind::Observable(k) => is_date(k.clone()),
+        _ => false,
+    }
+}
+
 pub(super) fn results_in_state(kind: Rc<ast::Kind>) -> bool {
     match kind.as_ref() {
         ast::Kind::Abstraction(_, s) => results_in_state(s.clone()),
-        ast::Kind::State => true,
+        ast::Kind::State(_) => true,
         ast::Kind::Unresolved(k) => k.borrow().clone().map_or(false, results_in_state),
         _ => false,
     }
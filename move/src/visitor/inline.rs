@@ -0,0 +1,222 @@
+use super::expression::results_in_state;
+use sprint_parser::ast;
+use std::{collections::HashMap, rc::Rc};
+
+/// Functions cannot be pushed onto the stack, so definitions applied to functions are inlined,
+/// and lambdas are applied to their arguments. Polymorphic definitions applied to contracts are
+/// inlined likewise. Definitions which take functions are then no
+/// longer used, and are removed. Lifted functions are likewise applied to the observables they
+/// are lifted over, whose values are computed wherever they are used.
+pub(super) fn visit<'a>(definitions: &[ast::Definition<'a>]) -> Vec<ast::Definition<'a>> {
+    let context: HashMap<_, _> = definitions
+        .iter()
+        .map(|definition| (definition.variable.name, definition))
+        .collect();
+
+    definitions
+        .iter()
+        .filter(|definition| !takes_function(&definition.expression))
+        .map(|definition| {
+            ast::Definition::new(
+                definition.variable.clone(),
+                visit_expression(&context, &[], &definition.expression),
+            )
+        })
+        .collect()
+}
+
+fn visit_expression<'a>(
+    definitions: &HashMap<&'a str, &ast::Definition<'a>>,
+    inlined: &[&'a str],
+    expression: &ast::Expression<'a>,
+) -> ast::Expression<'a> {
+    if let ast::ExpressionType::Observable(ast::Observable::Lift(f, arguments)) =
+        &expression.expression
+    {
+        let application = arguments.iter().fold(f.as_ref().clone(), |f, argument| {
+            let span = f.span;
+            ast::Expression::new(
+                ast::ExpressionType::Application(f.into(), argument.as_ref().clone().into()),
+                span,
+            )
+        });
+
+        return ast::Expression::new(
+            ast::ExpressionType::Observable(ast::Observable::Konst(
+                visit_expression(definitions, inlined, &application).into(),
+            )),
+            expression.span,
+        );
+    }
+
+    let mut abstraction = expression;
+    let mut arguments = Vec::new();
+
+    while let ast::ExpressionType::Application(f, argument) = &abstraction.expression {
+        abstraction = f;
+        arguments.push(argument.as_ref());
+    }
+
+    if arguments.is_empty() {
+        return expression.map(|e| visit_expression(definitions, inlined, e));
+    }
+
+    let arguments: Vec<_> = arguments
+        .into_iter()
+        .rev()
+        .map(|argument| visit_expression(definitions, inlined, argument))
+        .collect();
+
+    match &abstraction.expression {
+        ast::ExpressionType::Abstraction(_, _) => {
+            visit_expression(definitions, inlined, &apply(abstraction, arguments))
+        }
+        ast::ExpressionType::Variable(v) => match definitions.get(v.name) {
+            // Recursive definitions would be inlined forever, so they are only inlined once.
+            Some(definition)
+                if !inlined.contains(&v.name) && is_inlined(definition, v, &arguments) =>
+            {
+                let inlined = [inlined, &[v.name]].concat();
+                let expression = apply(&definition.expression, arguments);
+
+                visit_expression(definitions, &inlined, &expression)
+            }
+            _ => apply(abstraction, arguments),
+        },
+        _ => apply(
+            &visit_expression(definitions, inlined, abstraction),
+            arguments,
+        ),
+    }
+}
+
+/// Definitions are only inlined once where they are recursive, so recursive definitions which
+/// would be inlined cannot be generated.
+pub fn check(definitions: &[ast::Definition]) -> Result<(), String> {
+    fn find<'a, 'b>(
+        definitions: &HashMap<&'a str, &'b ast::Definition<'a>>,
+        expression: &'b ast::Expression<'a>,
+    ) -> Option<&'b ast::Variable<'a>> {
+        let mut abstraction = expression;
+        let mut arguments = Vec::new();
+
+        while let ast::ExpressionType::Application(f, argument) = &abstraction.expression {
+            abstraction = f;
+            arguments.push(argument.as_ref().clone());
+        }
+
+        if let ast::ExpressionType::Variable(v) = &abstraction.expression {
+            if let Some(definition) = definitions.get(v.name) {
+                if takes_function(&definition.expression) || is_inlined(definition, v, &arguments) {
+                    return Some(v);
+                }
+            }
+        }
+
+        expression
+            .children()
+            .into_iter()
+            .find_map(|child| find(definitions, child))
+    }
+
+    let context: HashMap<_, _> = definitions
+        .iter()
+        .map(|definition| (definition.variable.name, definition))
+        .collect();
+
+    match visit(definitions)
+        .iter()
+        .find_map(|definition| find(&context, &definition.expression).map(|v| v.name))
+    {
+        Some(name) => Err(format!(
+            "\"{}\" is recursive, so it cannot take functions, nor be used at contracts unless it \
+             is defined for them",
+            name
+        )),
+        None => Ok(()),
+    }
+}
+
+// Whether an application of a definition is inlined, which it is where it is applied to functions,
+// as they cannot be pushed onto the stack. Polymorphic definitions used at contracts are inlined
+// too, as their methods take words.
+fn is_inlined<'a>(
+    definition: &ast::Definition<'a>,
+    variable: &ast::Variable<'a>,
+    arguments: &[ast::Expression<'a>],
+) -> bool {
+    takes_function(&definition.expression)
+        || arguments.iter().any(|a| is_function(a.kind()))
+        || (!results_in_state(definition.variable.kind.clone())
+            && (results_in_state(variable.kind.clone())
+                || arguments.iter().any(|a| results_in_state(a.kind()))))
+}
+
+/// Applies an expression to arguments, substituting those taken by its abstractions.
+pub(super) fn apply<'a>(
+    mut abstraction: &ast::Expression<'a>,
+    arguments: Vec<ast::Expression<'a>>,
+) -> ast::Expression<'a> {
+    let mut arguments = arguments.into_iter().peekable();
+    let mut substitutions = HashMap::new();
+
+    while let (ast::ExpressionType::Abstraction(argument, e), Some(_)) =
+        (&abstraction.expression, arguments.peek())
+    {
+        substitutions.insert(argument.name, arguments.next().unwrap());
+        abstraction = e;
+    }
+
+    arguments.fold(abstraction.substitute(&substitutions), |f, argument| {
+        let span = f.span;
+        ast::Expression::new(
+            ast::ExpressionType::Application(f.into(), argument.into()),
+            span,
+        )
+    })
+}
+
+fn takes_function(mut expression: &ast::Expression) -> bool {
+    while let ast::ExpressionType::Abstraction(argument, e) = &expression.expression {
+        if is_function(argument.kind.clone()) {
+            return true;
+        }
+
+        expression = e;
+    }
+
+    false
+}
+
+fn is_function(kind: Rc<ast::Kind>) -> bool {
+    matches!(
+        ast::Kind::simplify(kind).as_ref(),
+        ast::Kind::Abstraction(_, _)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sprint_parser::parser::{contract, Arena};
+
+    #[test]
+    fn recursion() {
+        let recursive = |source| check(&contract(source, &Arena::new()).unwrap());
+
+        assert!(recursive("f c = and c (f c)\nmain = f (one \"USD\")").is_ok());
+        assert!(recursive(
+            "twice :: (a -> a) -> a -> a\ntwice f x = f (f x)\nmain = twice give (one \"USD\")"
+        )
+        .is_ok());
+
+        assert_eq!(
+            recursive("f g c = and (g c) (f g c)\nmain = f give (one \"USD\")"),
+            Err(
+                "\"f\" is recursive, so it cannot take functions, nor be used at contracts unless \
+                 it is defined for them"
+                    .to_string()
+            )
+        );
+    }
+}
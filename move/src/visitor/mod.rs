@@ -1,12 +1,17 @@
 pub mod argument;
 pub mod definitions;
 pub mod expression;
+pub mod inline;
+pub mod observation;
+pub mod scale;
 pub mod state;
 
 use self::definitions::TERMINAL_ID;
 use crate::{
+    fixed_point::FixedPoint,
     jog::{contract::Contract, method::Method, variable::Variable},
     numbers::Numbers,
+    oracle::Oracles,
 };
 use sprint_parser::ast;
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
@@ -17,16 +22,28 @@ struct Context<'a, 'b> {
     numbers: Rc<RefCell<Numbers>>,
     function_context: Option<FunctionContext<'a>>,
     functions: HashMap<*const ast::Expression<'a>, Rc<RefCell<Option<u64>>>>,
+    oracles: &'a Oracles,
+    fixed_point: FixedPoint,
+    // Whether the code of an observable evaluated by `observe` is being generated, which is given
+    // the fields of the context it uses rather than the context.
+    observing: bool,
 }
 
 impl<'a, 'b> Context<'a, 'b> {
-    pub fn new(definitions: impl Iterator<Item = Rc<&'b ast::Definition<'a>>>) -> Self {
+    pub fn new(
+        definitions: impl Iterator<Item = Rc<&'b ast::Definition<'a>>>,
+        oracles: &'a Oracles,
+        fixed_point: FixedPoint,
+    ) -> Self {
         Self {
             contract: Default::default(),
             definitions: definitions.map(|d| (d.variable.name, d)).collect(),
             numbers: Rc::new(Numbers::from(TERMINAL_ID + 1).into()),
             function_context: Default::default(),
             functions: Default::default(),
+            oracles,
+            fixed_point,
+            observing: false,
         }
     }
 }
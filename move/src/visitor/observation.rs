@@ -0,0 +1,54 @@
+use super::expression::results_in_state;
+use sprint_parser::ast;
+use std::collections::HashSet;
+
+/// Observables recorded on a schedule are stored in the context of a contract, and are recorded
+/// by methods which know nothing of the arguments of the definitions using them, so they can only
+/// be used by contracts, and can only depend on other definitions.
+pub fn check(definitions: &[ast::Definition]) -> Result<(), String> {
+    fn find<'a, 'b>(expression: &'b ast::Expression<'a>, found: &mut Vec<&'b ast::Expression<'a>>) {
+        if let ast::ExpressionType::Observable(ast::Observable::Observed(_, _, e)) =
+            &expression.expression
+        {
+            found.push(e);
+        }
+
+        for child in expression.children() {
+            find(child, found);
+        }
+    }
+
+    let names: HashSet<_> = definitions
+        .iter()
+        .map(|definition| definition.variable.name)
+        .collect();
+
+    for definition in definitions {
+        let mut found = Vec::new();
+        find(&definition.expression, &mut found);
+
+        if found.is_empty() {
+            continue;
+        }
+
+        if !results_in_state(definition.variable.kind.clone()) {
+            return Err(format!(
+                "\"{}\" records observations, so it must be a contract",
+                definition.variable.name
+            ));
+        }
+
+        if let Some(variable) = found
+            .into_iter()
+            .flat_map(|e| e.free_variables())
+            .find(|variable| !names.contains(variable.name))
+        {
+            return Err(format!(
+                "The observations recorded by \"{}\" cannot depend on \"{}\"",
+                definition.variable.name, variable.name
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use super::{expression::is_decimal, inline::apply};
+use crate::fixed_point::FixedPoint;
+use sprint_parser::ast::{self, state::Effect};
+use std::collections::{HashMap, HashSet};
+
+/// The scale of a contract is multiplied by each scalar it is scaled by, so it can be bounded
+/// along each path from `main` on which the scalars are constant. Paths on which the scale is
+/// certain to overflow are rejected, rather than aborting with code 4 once they are taken.
+/// Recursive definitions are followed once along a path, beyond which the scale is unknown.
+pub fn check(definitions: &[ast::Definition], fixed_point: &FixedPoint) -> Result<(), String> {
+    let definitions: HashMap<_, _> = definitions
+        .iter()
+        .map(|definition| (definition.variable.name, definition))
+        .collect();
+
+    let bound = Bound {
+        recursive: recursive(&definitions),
+        definitions,
+        fixed_point,
+        unit: fixed_point.unit().unwrap(),
+    };
+
+    match bound.definitions.get("main") {
+        Some(main) => bound.visit(&main.expression, bound.unit, &[]),
+        None => Ok(()),
+    }
+}
+
+struct Bound<'a, 'b> {
+    definitions: HashMap<&'a str, &'b ast::Definition<'a>>,
+    recursive: HashSet<&'a str>,
+    fixed_point: &'b FixedPoint,
+    unit: u64,
+}
+
+impl<'a> Bound<'a, '_> {
+    fn visit(
+        &self,
+        expression: &ast::Expression<'a>,
+        scale: u64,
+        expanded: &[&'a str],
+    ) -> Result<(), String> {
+        let mut f = expression;
+        let mut arguments = Vec::new();
+
+        while let ast::ExpressionType::Application(g, argument) = &f.expression {
+            f = g;
+            arguments.push(argument.as_ref().clone());
+        }
+
+        arguments.reverse();
+
+        match &f.expression {
+            ast::ExpressionType::State(state) => {
+                'transitions: for transition in state.transitions() {
+                    let mut scale = scale;
+
+                    for effect in transition.effects() {
+                        match effect {
+                            Effect::Scale(scalar) => match self.constant(scalar) {
+                                Some(scalar) => scale = self.multiply(scale, scalar)?,
+                                None => continue 'transitions,
+                            },
+                            Effect::Spawn(e) => self.visit(e, scale, expanded)?,
+                            _ => {}
+                        }
+                    }
+
+                    self.visit(transition.next(), scale, expanded)?;
+                }
+
+                Ok(())
+            }
+            ast::ExpressionType::Abstraction(_, _) if !arguments.is_empty() => {
+                self.visit(&apply(f, arguments), scale, expanded)
+            }
+            ast::ExpressionType::Variable(v) if !expanded.contains(&v.name) => {
+                match self.definitions.get(v.name) {
+                    Some(definition) if self.recursive.contains(v.name) => self.visit(
+                        &apply(&definition.expression, arguments),
+                        scale,
+                        &[expanded, &[v.name]].concat(),
+                    ),
+                    Some(definition) => {
+                        self.visit(&apply(&definition.expression, arguments), scale, expanded)
+                    }
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Multiplies the scale by a scalar as the generated `multiply` does, unless it overflows.
+    fn multiply(&self, scale: u64, scalar: u64) -> Result<u64, String> {
+        match self.fixed_point.multiply(scale, scalar) {
+            Some(product) => Ok(product),
+            None => Err(format!(
+                "Scaling by {} overflows the scale of the contract, which is already {}",
+                self.decimal(scalar),
+                self.decimal(scale)
+            )),
+        }
+    }
+
+    /// The value of a word or decimal expression which is known before the contract is
+    /// initialized, with decimals represented as they are at runtime.
+    fn constant(&self, expression: &ast::Expression<'a>) -> Option<u64> {
+        match &expression.expression {
+            ast::ExpressionType::Word(w) => Some(*w),
+            ast::ExpressionType::Decimal(d) => self.fixed_point.literal(d),
+            ast::ExpressionType::Amount(_, e) => self.constant(e),
+            ast::ExpressionType::Observable(ast::Observable::Konst(e)) => self.constant(e),
+            ast::ExpressionType::Observable(ast::Observable::Fixed(e)) => {
+                match is_decimal(e.kind()) {
+                    true => self.constant(e),
+                    false => self.constant(e)?.checked_mul(self.unit),
+                }
+            }
+            ast::ExpressionType::Class(class @ ast::Class::Numerable(n)) => {
+                let decimal = is_decimal(class.operand_kind());
+
+                match n {
+                    ast::Numerable::Add(left, right, _) => {
+                        self.constant(left)?.checked_add(self.constant(right)?)
+                    }
+                    ast::Numerable::Subtract(left, right, _) => {
+                        self.constant(left)?.checked_sub(self.constant(right)?)
+                    }
+                    ast::Numerable::Multiply(left, right, _) => {
+                        let (left, right) = (self.constant(left)?, self.constant(right)?);
+
+                        match decimal {
+                            true => self.fixed_point.multiply(left, right),
+                            false => left.checked_mul(right),
+                        }
+                    }
+                    ast::Numerable::Divide(left, right) => {
+                        let (left, right) = (self.constant(left)?, self.constant(right)?);
+
+                        match (decimal, right) {
+                            (_, 0) => None,
+                            (true, _) => self.fixed_point.ratio(left, right),
+                            (false, _) => Some(left / right),
+                        }
+                    }
+                }
+            }
+            ast::ExpressionType::Variable(v) if !self.recursive.contains(v.name) => self
+                .definitions
+                .get(v.name)
+                .and_then(|definition| self.constant(&definition.expression)),
+            _ => None,
+        }
+    }
+
+    fn decimal(&self, digits: u64) -> ast::Decimal {
+        ast::Decimal {
+            digits,
+            places: self.fixed_point.precision,
+        }
+    }
+}
+
+/// The definitions which depend on themselves.
+fn recursive<'a>(definitions: &HashMap<&'a str, &ast::Definition<'a>>) -> HashSet<&'a str> {
+    definitions
+        .keys()
+        .copied()
+        .filter(|name| {
+            let mut stack = vec![*name];
+            let mut seen = HashSet::new();
+
+            while let Some(next) = stack.pop() {
+                for variable in definitions[next].expression.free_variables() {
+                    if variable.name == *name {
+                        return true;
+                    }
+
+                    if definitions.contains_key(variable.name) && seen.insert(variable.name) {
+                        stack.push(variable.name);
+                    }
+                }
+            }
+
+            false
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sprint_parser::parser::{contract, Arena};
+
+    fn bound(source: &str) -> Result<(), String> {
+        check(
+            &contract(source, &Arena::new()).unwrap(),
+            &FixedPoint::default(),
+        )
+    }
+
+    #[test]
+    fn overflow() {
+        assert!(bound("main = scale (konst 1000) (scale (konst 1000) (one \"USD\"))").is_ok());
+        assert!(
+            bound("main = scale (obs \"USD/EUR\") (scale (konst 10000000000) (one \"USD\"))")
+                .is_ok()
+        );
+        assert!(bound("f = scale (konst 2) (when (konst True) f)\nmain = f").is_ok());
+
+        assert!(bound("main = scale (konst 20000000) (one \"USD\")").is_ok());
+        assert!(bound("main = scale (konst 0.5) (scale (konst 20000000) (one \"USD\"))").is_ok());
+
+        assert_eq!(
+            bound("f x = scale (konst 100000) x\nmain = give (f (f (f (one \"USD\"))))"),
+            Err(
+                "Scaling by 100000.000000 overflows the scale of the contract, which is already \
+                 10000000000.000000"
+                    .to_string()
+            )
+        );
+        assert!(bound(
+            "k = konst 4000000000\nmain = and (one \"USD\") (scale k (scale k (one \"USD\")))"
+        )
+        .is_err());
+        assert!(
+            bound("main = scale (konst (2 * 5000000000)) (scale (konst 10000) (one \"USD\"))")
+                .is_err()
+        );
+    }
+}
@@ -2,9 +2,16 @@ use super::{definitions::TERMINAL_ID, expression, Context};
 use crate::{
     jog::{
         action::{
-            assert::Assert, flip::Flip, libra::Withdraw, push::Push, scale::Scale, spawn::Spawn,
+            assert::Assert,
+            flip::{Flip, GiveTo},
+            guard::Guard,
+            libra::Withdraw,
+            pay_to::PayTo,
+            push::Push,
+            scale::Scale,
+            spawn::Spawn,
         },
-        expression::{Address, Expression},
+        expression::Expression,
         identifier::Identifier,
         kind::Kind,
         method::Method,
@@ -21,22 +28,57 @@ pub(super) fn visit<'a>(context: &mut Context<'a, '_>, state: &ast::state::State
     }
 
     let from = context.numbers.borrow_mut().next().unwrap();
+    let branches = state.transitions().len() > 1;
+    let mut horizons = Some(Vec::new());
 
-    for transition in state.transitions() {
+    for (index, transition) in state.transitions().iter().enumerate() {
         let to = expression::visit(context, transition.next());
 
         let function_context = context.function_context.as_ref().unwrap();
-        let mut method = Method::transition(function_context.name, from, to);
+
+        // States with several transitions need a distinctly named method for each of them.
+        let identifier = if branches {
+            Identifier::Branch(function_context.name, index as u64)
+        } else {
+            Identifier::Transition(function_context.name)
+        };
+
+        let mut method = Method::transition(identifier, from, to);
 
         for condition in transition.conditions() {
             method.add_action(Assert::new(expression::visit(context, condition), 0));
         }
 
+        horizons = match (horizons, transition.horizon()) {
+            (Some(mut horizons), Some(horizon)) => {
+                for date in horizon {
+                    horizons.push(expression::visit(context, &date));
+                }
+
+                Some(horizons)
+            }
+            _ => None,
+        };
+
         let mut spawn_numbers = Numbers::default();
 
         for effect in transition.effects() {
             match effect {
                 ast::state::Effect::Flip => method.add_action(Flip::default()),
+                ast::state::Effect::GiveTo(role) => {
+                    method.add_action(GiveTo::new(expression::visit(context, role)))
+                }
+                ast::state::Effect::Guard(condition) => {
+                    // Guards hold the observable as it is passed on the stack, which is evaluated
+                    // whenever the contract is transitioned, as the positions on the stack it
+                    // refers to outlive the state that introduced it.
+                    let observable = expression::visit_passed_observable(context, condition);
+
+                    method.add_action(Guard::new(observable))
+                }
+                ast::state::Effect::PayTo(payee) => {
+                    method.add_action(PayTo::new(expression::visit(context, payee)))
+                }
                 ast::state::Effect::Scale(scalar) => {
                     method.add_action(Scale::new(expression::visit(context, scalar)))
                 }
@@ -67,12 +109,19 @@ pub(super) fn visit<'a>(context: &mut Context<'a, '_>, state: &ast::state::State
                         Expression::Identifier(spawned_context.identifier().clone()).r#move(),
                     ));
                 }
-                ast::state::Effect::Withdraw => method.add_action(Withdraw::new(Address::Party)),
+                ast::state::Effect::Withdraw(currency) => {
+                    method.add_action(Withdraw::new(expression::visit(context, currency)))
+                }
             }
         }
 
         context.contract.add_method(method);
     }
 
+    // A state expires once none of its transitions can be taken anymore.
+    if let Some(horizon) = horizons.and_then(|horizons| expression::fold("maximum", horizons)) {
+        context.contract.add_horizon(from, horizon);
+    }
+
     from
 }
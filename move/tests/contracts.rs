@@ -7,11 +7,10 @@ use functional_tests::{
     evaluator::eval,
     preprocessor::{build_transactions, split_input},
 };
-use sprint_move::generate;
-use sprint_parser::parser::contract;
+use sprint_move::{generate, Config, Oracle};
+use sprint_parser::parser::{contract, Arena};
 use std::{
     env,
-    fmt::Display,
     fs::{self, File},
     io::{Read, Write},
     iter,
@@ -20,66 +19,478 @@ use std::{
 use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
 
 pub static DATE: &str = "tests/observables/date.mvir";
+pub static ORACLE: &str = "tests/observables/oracle.mvir";
 
 #[test]
 fn zero() {
     let suite = Path::new("tests/tests/zero.mvir");
-    let contract = contract("main = zero").unwrap();
-
-    test(generate(&contract), &[], suite);
+    test("main = zero", &[], suite);
 }
 
 #[test]
 fn one() {
     let suite = Path::new("tests/tests/one.mvir");
-    let contract = contract("main = one").unwrap();
-
-    test(generate(&contract), &[], suite);
+    test("main = one \"USD\"", &[], suite);
 }
 
 #[test]
 fn scale() {
     let suite = Path::new("tests/tests/scale.mvir");
-    let contract = contract("main = scale (konst 5) one").unwrap();
+    test("main = scale (konst 5) (one \"USD\")", &[], suite);
+}
+
+#[test]
+fn large_scale() {
+    let suite = Path::new("tests/tests/large_scale.mvir");
+    test(
+        "main = scale (konst 20000000) (scale (konst 0.000001) (one \"USD\"))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn arithmetic() {
+    let suite = Path::new("tests/tests/arithmetic.mvir");
+    test(
+        "main = scale (konst ((1 + 2) * 3 - 8 / 2)) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn comparison() {
+    let suite = Path::new("tests/tests/comparison.mvir");
+    test(
+        "main = cond (konst (1 < 2 && (2 == 3 || 4 == 4))) (one \"USD\") zero",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn booleans() {
+    let suite = Path::new("tests/tests/booleans.mvir");
+    test(
+        "flip :: Bool -> Bool\nflip b = not b\n\
+         pick :: Bool -> Contract\npick b = cond (konst (flip b && True)) (one \"USD\") zero\n\
+         main = pick False",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn decimal() {
+    let suite = Path::new("tests/tests/decimal.mvir");
+    test(
+        "main = scale (konst 2.5 * konst 1.5) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
 
-    test(generate(&contract), &[], suite);
+#[test]
+fn ratio() {
+    let suite = Path::new("tests/tests/ratio.mvir");
+    test(
+        "main = scale (konst 30000000.0 / konst 20000000.0) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn lift() {
+    let suite = Path::new("tests/tests/lift.mvir");
+    test(
+        "main = scale (lift2 (\\x y -> x + y) (max (konst 4) (konst 3)) \
+         (min (konst 1) (konst 2))) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn oracle() {
+    let oracle = Path::new(ORACLE);
+    let suite = Path::new("tests/tests/oracle.mvir");
+    test(
+        "main = scale (obs \"USD/EUR\") (one \"USD\")",
+        &[oracle],
+        suite,
+    );
+}
+
+#[test]
+fn average() {
+    let date = Path::new(DATE);
+    let oracle = Path::new(ORACLE);
+    let suite = Path::new("tests/tests/average.mvir");
+    test(
+        "main = scale (average 2020-01-01T00:00:00Z 1d 3 (obs \"USD/EUR\")) (one \"USD\")",
+        &[date, oracle],
+        suite,
+    );
 }
 
 #[test]
 fn before() {
     let date = Path::new(DATE);
     let suite = Path::new("tests/tests/before.mvir");
-    let contract = contract("main = before 2020-12-25T00:00:00Z one").unwrap();
-
-    test(generate(&contract), &[date], suite);
+    test(
+        "main = before 2020-12-25T00:00:00Z (one \"USD\")",
+        &[date],
+        suite,
+    );
 }
 
 #[test]
 fn after() {
     let date = Path::new(DATE);
     let suite = Path::new("tests/tests/after.mvir");
-    let contract = contract("main = after 2020-12-25T00:00:00Z one").unwrap();
+    test(
+        "main = after 2020-12-25T00:00:00Z (one \"USD\")",
+        &[date],
+        suite,
+    );
+}
 
-    test(generate(&contract), &[date], suite);
+#[test]
+fn truncate() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/truncate.mvir");
+    test(
+        "main = truncate 2020-12-25T00:00:00Z (or (one \"USD\") zero)",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn duration() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/duration.mvir");
+    test(
+        "main = after (2020-12-25T00:00:00Z + 1y) (one \"USD\")",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn months() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/months.mvir");
+    test(
+        "main :: Date -> Duration -> Contract\nmain t d = after (t + d) (one \"USD\")",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn then() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/then.mvir");
+    test(
+        "main = then (truncate 2020-12-25T00:00:00Z (one \"USD\")) \
+         (truncate 2021-12-25T00:00:00Z (scale (konst 2) (one \"USD\")))",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn nested_horizon() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/nested_horizon.mvir");
+    test(
+        "main = then (scale (konst 2) (truncate 2020-01-01T00:00:00Z (one \"USD\"))) \
+         (truncate 2021-01-01T00:00:00Z (one \"USD\"))",
+        &[date],
+        suite,
+    );
 }
 
 #[test]
 fn and() {
     let suite = Path::new("tests/tests/and.mvir");
-    let contract = contract("main = and one one").unwrap();
+    test("main = and (one \"USD\") (one \"USD\")", &[], suite);
+}
+
+#[test]
+fn cond() {
+    let suite = Path::new("tests/tests/cond.mvir");
+    test("main = cond (konst (1 < 2)) (one \"USD\") zero", &[], suite);
+}
+
+#[test]
+fn observed_cond() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/observed_cond.mvir");
+    test(
+        "main = cond (obs \"Date\" > konst 1577318400) (one \"USD\") zero",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn or() {
+    let suite = Path::new("tests/tests/or.mvir");
+    test(
+        "main = or (one \"USD\") (scale (konst 2) (one \"USD\"))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn when() {
+    let suite = Path::new("tests/tests/when.mvir");
+    test("main = when (konst (1 < 2)) (one \"USD\")", &[], suite);
+}
+
+#[test]
+fn until() {
+    let suite = Path::new("tests/tests/until.mvir");
+    test("main = until (konst (2 < 1)) (one \"USD\")", &[], suite);
+}
+
+#[test]
+fn changing_conditions() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/changing_conditions.mvir");
+    test(
+"main = until (obs \"Date\" > konst 1577836800) (when (obs \"Date\" > konst 1577318400) (one \"USD\"))", &[date], suite);
+}
 
-    test(generate(&contract), &[], suite);
+#[test]
+fn polymorphism() {
+    let suite = Path::new("tests/tests/polymorphism.mvir");
+    test(
+        "same :: a -> a\nsame x = x\n\
+         main = cond (konst (same True)) (scale (konst (same 2)) (same (one \"USD\"))) zero",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn local() {
+    let suite = Path::new("tests/tests/local.mvir");
+    test(
+        "scaleK x c = scale (konst y) c where y = x - 1\n\
+         main = let k = 3 in scaleK k (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn lambda() {
+    let suite = Path::new("tests/tests/lambda.mvir");
+    test(
+        "twice :: (a -> a) -> a -> a\ntwice f x = f (f x)\n\
+         main = twice (\\c -> scale (konst 2) c) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn capture() {
+    let suite = Path::new("tests/tests/capture.mvir");
+    test(
+        "twice :: (a -> a) -> a -> a\ntwice f x = f (f x)\n\
+         f x = let g = \\c -> scale (konst x) c in twice (\\x -> g x) (one \"USD\")\n\
+         main = f 2",
+        &[],
+        suite,
+    );
 }
 
 #[test]
 fn nested() {
     let suite = Path::new("tests/tests/nested.mvir");
-    let contract = contract("main = give (give (and one one))").unwrap();
+    test(
+        "main = give (give (and (one \"USD\") (one \"USD\")))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn zcb() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/zcb.mvir");
+    test("main = zcb 2020-12-25T00:00:00Z 10 \"USD\"", &[date], suite);
+}
 
-    test(generate(&contract), &[], suite);
+#[test]
+fn european() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/european.mvir");
+    test(
+        "main = european 2020-12-25T00:00:00Z (scale (konst 5) (one \"USD\"))",
+        &[date],
+        suite,
+    );
 }
 
-fn test(module: impl Display, observables: &[&Path], test: &Path) {
+#[test]
+fn american() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/american.mvir");
+    test(
+        "main = american 2020-12-25T00:00:00Z 2021-12-25T00:00:00Z (one \"USD\")",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn forward() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/forward.mvir");
+    test(
+        "main = forward 2020-12-25T00:00:00Z 3 \"USD\" (scale (konst 5) (one \"USD\"))",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn bond() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/bond.mvir");
+    test(
+        "main = bond 2020-12-25T00:00:00Z 1y 2 5 100 \"USD\"",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn schedule() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/schedule.mvir");
+    test(
+        "main = coupon 2020-12-25T00:00:00Z 5 \"USD\"\n  \
+           (coupon 2021-12-25T00:00:00Z 5 \"USD\" (zcb 2022-12-25T00:00:00Z 105 \"USD\"))",
+        &[date],
+        suite,
+    );
+}
+
+#[test]
+fn swap() {
+    let suite = Path::new("tests/tests/swap.mvir");
+    test(
+        "main = swap (scale (konst 3) (one \"USD\")) (scale (konst 2) (one \"USD\"))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn currencies() {
+    let suite = Path::new("tests/tests/currencies.mvir");
+    test(
+        "main = and (one \"EUR\") (give (scale (konst 2) (one \"USD\")))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn exchange() {
+    let oracle = Path::new(ORACLE);
+    let suite = Path::new("tests/tests/exchange.mvir");
+    test(
+        "main = scale (konst (amount \"USD\" 2.0) * fx \"USD\" \"EUR\") (one \"EUR\")",
+        &[oracle],
+        suite,
+    );
+}
+
+#[test]
+fn pay_to() {
+    let suite = Path::new("tests/tests/pay_to.mvir");
+    test("main = give (payTo 0xA550C18 (one \"USD\"))", &[], suite);
+}
+
+#[test]
+fn roles() {
+    let suite = Path::new("tests/tests/roles.mvir");
+    test(
+        "roles lender borrower agent\nmain = giveTo agent (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn give_to() {
+    let suite = Path::new("tests/tests/give_to.mvir");
+    test(
+        "roles lender borrower agent\nmain = giveTo agent (give (one \"USD\"))",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn is_role() {
+    let suite = Path::new("tests/tests/is_role.mvir");
+    test(
+        "roles lender borrower agent\nmain = cond (isRole agent) (one \"USD\") zero",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn arguments() {
+    let suite = Path::new("tests/tests/arguments.mvir");
+    test(
+        "main :: Word -> Contract\nmain k = scale (konst k) (one \"USD\")",
+        &[],
+        suite,
+    );
+}
+
+#[test]
+fn decimal_arguments() {
+    let date = Path::new(DATE);
+    let suite = Path::new("tests/tests/decimal_arguments.mvir");
+    test(
+        "main :: Decimal -> Date -> Contract\n\
+         main party maturity = before maturity (scale (konst party) (scale (konst 2) (one \"USD\")))",
+        &[date],
+        suite,
+    );
+}
+
+// The observables of the tests are provided by the modules in `tests/observables`.
+fn config() -> Config {
+    let mut config = Config::default();
+    config
+        .oracles
+        .insert("Date".into(), Oracle::new("{{alice}}.Date", "{{alice}}"));
+    config
+        .oracles
+        .insert("USD/EUR".into(), Oracle::new("{{alice}}.Oracle", "{{bob}}"));
+    config
+}
+
+fn test(source: &str, observables: &[&Path], test: &Path) {
+    let arena = Arena::new();
+    let contract = contract(source, &arena).unwrap();
+    let module = generate(&contract, &config()).unwrap();
+
     let mut input = String::new();
 
     input.push_str("//! account: alice, 1000000\n");
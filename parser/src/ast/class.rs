@@ -3,12 +3,155 @@ use std::rc::Rc;
 
 #[derive(Clone, Debug)]
 pub enum Class<'a> {
+    /// The boolean operators, which are not overloaded, so that they are only applied to booleans
+    /// or observable booleans.
+    Boolean(Boolean<'a>),
     Comparable(Comparable<'a>),
     Equatable(Equatable<'a>),
     Negatable(Negatable<'a>),
     Numerable(Numerable<'a>),
 }
 
+impl<'a> Class<'a> {
+    /// The name of the corresponding Haskell type class, used in diagnostics, unless the class is
+    /// of operators which are not overloaded.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Boolean(_) => None,
+            Self::Comparable(_) => Some("Ord"),
+            Self::Equatable(_) => Some("Eq"),
+            Self::Negatable(_) | Self::Numerable(_) => Some("Num"),
+        }
+    }
+
+    pub fn operands(&self) -> Vec<&Expression<'a>> {
+        match self {
+            Self::Comparable(Comparable::Greater(l, r))
+            | Self::Comparable(Comparable::Less(l, r))
+            | Self::Comparable(Comparable::GreaterEqual(l, r))
+            | Self::Comparable(Comparable::LessEqual(l, r))
+            | Self::Equatable(Equatable::Equal(l, r))
+            | Self::Equatable(Equatable::NotEqual(l, r))
+            | Self::Boolean(Boolean::And(l, r))
+            | Self::Boolean(Boolean::Or(l, r))
+            | Self::Numerable(Numerable::Add(l, r, _))
+            | Self::Numerable(Numerable::Subtract(l, r, _))
+            | Self::Numerable(Numerable::Multiply(l, r, _))
+            | Self::Numerable(Numerable::Divide(l, r)) => vec![l, r],
+            Self::Boolean(Boolean::Not(e)) | Self::Negatable(Negatable::Negate(e)) => vec![e],
+        }
+    }
+
+    /// Rebuilds the class with each of its operands mapped.
+    pub fn map(&self, f: impl Fn(&Expression<'a>) -> Expression<'a>) -> Self {
+        let f = |e: &Expression<'a>| Box::new(f(e));
+
+        match self {
+            Self::Comparable(Comparable::Greater(l, r)) => {
+                Self::Comparable(Comparable::Greater(f(l), f(r)))
+            }
+            Self::Comparable(Comparable::Less(l, r)) => {
+                Self::Comparable(Comparable::Less(f(l), f(r)))
+            }
+            Self::Comparable(Comparable::GreaterEqual(l, r)) => {
+                Self::Comparable(Comparable::GreaterEqual(f(l), f(r)))
+            }
+            Self::Comparable(Comparable::LessEqual(l, r)) => {
+                Self::Comparable(Comparable::LessEqual(f(l), f(r)))
+            }
+            Self::Equatable(Equatable::Equal(l, r)) => {
+                Self::Equatable(Equatable::Equal(f(l), f(r)))
+            }
+            Self::Equatable(Equatable::NotEqual(l, r)) => {
+                Self::Equatable(Equatable::NotEqual(f(l), f(r)))
+            }
+            Self::Boolean(Boolean::And(l, r)) => Self::Boolean(Boolean::And(f(l), f(r))),
+            Self::Boolean(Boolean::Or(l, r)) => Self::Boolean(Boolean::Or(f(l), f(r))),
+            Self::Boolean(Boolean::Not(e)) => Self::Boolean(Boolean::Not(f(e))),
+            Self::Negatable(Negatable::Negate(e)) => Self::Negatable(Negatable::Negate(f(e))),
+            Self::Numerable(Numerable::Add(l, r, k)) => {
+                Self::Numerable(Numerable::Add(f(l), f(r), k.clone()))
+            }
+            Self::Numerable(Numerable::Subtract(l, r, k)) => {
+                Self::Numerable(Numerable::Subtract(f(l), f(r), k.clone()))
+            }
+            Self::Numerable(Numerable::Multiply(l, r, k)) => {
+                Self::Numerable(Numerable::Multiply(f(l), f(r), k.clone()))
+            }
+            Self::Numerable(Numerable::Divide(l, r)) => {
+                Self::Numerable(Numerable::Divide(f(l), f(r)))
+            }
+        }
+    }
+
+    /// The kind shared by all of the operands.
+    pub fn operand_kind(&self) -> Rc<Kind> {
+        self.operands()[0].kind()
+    }
+
+    /// Whether the class multiplies an amount by an exchange rate to convert it to another
+    /// currency, e.g. `konst (amount "USD" 100.0) * fx "USD" "EUR"`, which is known once the kind
+    /// of either operand is.
+    pub fn is_exchange(&self) -> bool {
+        fn rate(kind: Rc<Kind>) -> bool {
+            match Kind::simplify(kind).as_ref() {
+                Kind::Observable(k) => rate(k.clone()),
+                Kind::Rate(_, _) => true,
+                _ => false,
+            }
+        }
+
+        match self {
+            Self::Numerable(Numerable::Multiply(l, r, _)) => rate(l.kind()) || rate(r.kind()),
+            _ => false,
+        }
+    }
+
+    /// The kind of the result, which is observable if the operands are.
+    pub fn kind(&self) -> Rc<Kind> {
+        match self {
+            Self::Comparable(_) | Self::Equatable(_) => {
+                match Kind::simplify(self.operand_kind()).as_ref() {
+                    Kind::Observable(_) => Kind::Observable(Kind::Boolean.into()).into(),
+                    _ => Kind::Boolean.into(),
+                }
+            }
+            Self::Numerable(Numerable::Add(_, _, k))
+            | Self::Numerable(Numerable::Subtract(_, _, k))
+            | Self::Numerable(Numerable::Multiply(_, _, k)) => k.clone(),
+            Self::Boolean(_) | Self::Negatable(_) | Self::Numerable(_) => self.operand_kind(),
+        }
+    }
+
+    /// Whether the class has an instance for the kind. Unresolved kinds are assumed to have one.
+    pub fn is_instance(&self, kind: &Kind) -> bool {
+        match kind {
+            Kind::Boolean => matches!(self, Self::Equatable(_) | Self::Boolean(_)),
+            Kind::Address | Kind::Currency(_) | Kind::Role => matches!(self, Self::Equatable(_)),
+            // Amounts are only multiplied by exchange rates, which are checked once the kinds of
+            // the operands are resolved.
+            Kind::Amount(_) | Kind::Date | Kind::Duration => matches!(
+                self,
+                Self::Comparable(_)
+                    | Self::Equatable(_)
+                    | Self::Numerable(Numerable::Add(_, _, _))
+                    | Self::Numerable(Numerable::Subtract(_, _, _))
+            ),
+            Kind::Observable(k) => self.is_instance(&Kind::simplify(k.clone())),
+            Kind::Unresolved(_) => true,
+            Kind::Decimal | Kind::Word => !matches!(self, Self::Boolean(_)),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Boolean<'a> {
+    And(Box<Expression<'a>>, Box<Expression<'a>>),
+    Or(Box<Expression<'a>>, Box<Expression<'a>>),
+    Not(Box<Expression<'a>>),
+}
+
 #[derive(Clone, Debug)]
 pub enum Comparable<'a> {
     Greater(Box<Expression<'a>>, Box<Expression<'a>>),
@@ -30,18 +173,13 @@ pub enum Negatable<'a> {
 
 #[derive(Clone, Debug)]
 pub enum Numerable<'a> {
-    Add(Box<Expression<'a>>, Box<Expression<'a>>),
-    Subtract(Box<Expression<'a>>, Box<Expression<'a>>),
-    Multiply(Box<Expression<'a>>, Box<Expression<'a>>),
+    /// Adds words or durations, or a duration to a date, with the kind of the result.
+    Add(Box<Expression<'a>>, Box<Expression<'a>>, Rc<Kind>),
+    /// Subtracts words or durations, or a duration or another date from a date, with the kind of
+    /// the result.
+    Subtract(Box<Expression<'a>>, Box<Expression<'a>>, Rc<Kind>),
+    /// Multiplies words or decimals, or an amount by an exchange rate to convert it to the other
+    /// currency, with the kind of the result.
+    Multiply(Box<Expression<'a>>, Box<Expression<'a>>, Rc<Kind>),
     Divide(Box<Expression<'a>>, Box<Expression<'a>>),
 }
-
-impl Numerable<'_> {
-    pub(super) fn kind(&self) -> Rc<Kind> {
-        match self {
-            Self::Add(e, _) | Self::Subtract(e, _) | Self::Multiply(e, _) | Self::Divide(e, _) => {
-                e.kind()
-            }
-        }
-    }
-}
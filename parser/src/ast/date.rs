@@ -1,5 +1,74 @@
+use super::Expression;
+use chrono::{DateTime, Months};
+use std::convert::TryFrom;
+
 #[derive(Clone, Debug)]
-pub enum Date {
+pub enum Date<'a> {
     Now,
-    Date(u64, u64, u64, u64, u64, u64),
+    /// A number of seconds since the Unix epoch, in UTC.
+    Date(u64),
+    /// The latest date at which a contract can be acquired.
+    Horizon(Box<Expression<'a>>),
+    /// The earliest of several dates.
+    Earliest(Vec<Expression<'a>>),
+    /// The latest of several dates.
+    Latest(Vec<Expression<'a>>),
+}
+
+/// A number of calendar months followed by a number of seconds, so that a month after the last day
+/// of a month is the last day of the next, e.g. `1y + 1m` is 13 months while `30d` is 2592000
+/// seconds.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Duration {
+    pub months: u64,
+    pub seconds: u64,
+}
+
+impl Duration {
+    /// Durations are represented by words holding their months above this, and their seconds
+    /// below it.
+    pub const MONTH: u64 = 1 << 40;
+
+    /// The word representing the duration, or `None` if it is out of range.
+    pub fn word(self) -> Option<u64> {
+        if self.seconds >= Self::MONTH {
+            return None;
+        }
+
+        self.months
+            .checked_mul(Self::MONTH)?
+            .checked_add(self.seconds)
+    }
+
+    /// The duration repeated a number of times, or `None` if it is out of range.
+    pub fn times(self, count: u64) -> Option<Self> {
+        let duration = Self {
+            months: self.months.checked_mul(count)?,
+            seconds: self.seconds.checked_mul(count)?,
+        };
+
+        duration.word().map(|_| duration)
+    }
+
+    /// The date the duration after another, adding its months before its seconds, where the day of
+    /// the month is clamped to the length of the month it falls in. Dates are given and returned
+    /// as seconds since the Unix epoch, or `None` if they are out of range.
+    pub fn after(self, date: u64) -> Option<u64> {
+        let date = DateTime::from_timestamp(i64::try_from(date).ok()?, 0)?
+            .checked_add_months(Months::new(u32::try_from(self.months).ok()?))?;
+
+        u64::try_from(date.timestamp())
+            .ok()?
+            .checked_add(self.seconds)
+    }
+
+    /// The date the duration before another, subtracting its seconds before its months, as the
+    /// reverse of `after`.
+    pub fn before(self, date: u64) -> Option<u64> {
+        let date = i64::try_from(date.checked_sub(self.seconds)?).ok()?;
+        let date = DateTime::from_timestamp(date, 0)?
+            .checked_sub_months(Months::new(u32::try_from(self.months).ok()?))?;
+
+        u64::try_from(date.timestamp()).ok()
+    }
 }
@@ -8,10 +8,27 @@ use std::{
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Kind {
     Abstraction(Rc<Self>, Rc<Self>),
+    /// The address of an account, which can be paid.
+    Address,
+    /// A decimal quantity of a unit, e.g. `Amount USD`.
+    Amount(Rc<Self>),
     Boolean,
+    /// A currency, which is its own unit.
+    Currency(Rc<Self>),
     Date,
+    Decimal,
+    Duration,
     Observable(Rc<Self>),
-    State,
+    /// The decimal rate at which amounts of a unit exchange for amounts of another, e.g.
+    /// `Rate USD EUR`.
+    Rate(Rc<Self>, Rc<Self>),
+    /// One of the roles declared by a program, which are bound to addresses when a contract is
+    /// initialized.
+    Role,
+    /// A contract, paying in a unit which is the mixed unit `*` if it pays in several.
+    State(Rc<Self>),
+    /// A unit, such as the currency `USD`.
+    Unit(String),
     Unresolved(RefCell<Option<Rc<Self>>>),
     Word,
 }
@@ -34,6 +51,71 @@ impl Kind {
         kind
     }
 
+    /// Copies a kind, replacing each of its unresolved kinds with a fresh one.
+    pub fn instantiate(kind: Rc<Kind>) -> Rc<Kind> {
+        Self::instantiate_with(kind, &mut HashMap::new())
+    }
+
+    /// Copies kinds together, replacing each of their unresolved kinds with a fresh one which is
+    /// shared between the copies.
+    pub fn instantiate_all(kinds: Vec<Rc<Kind>>) -> Vec<Rc<Kind>> {
+        let mut fresh = HashMap::new();
+
+        kinds
+            .into_iter()
+            .map(|kind| Self::instantiate_with(kind, &mut fresh))
+            .collect()
+    }
+
+    fn instantiate_with(kind: Rc<Kind>, fresh: &mut HashMap<*const Kind, Rc<Kind>>) -> Rc<Kind> {
+        let kind = Self::simplify(kind);
+
+        match kind.as_ref() {
+            Self::Abstraction(from, to) => Self::Abstraction(
+                Self::instantiate_with(from.clone(), fresh),
+                Self::instantiate_with(to.clone(), fresh),
+            )
+            .into(),
+            Self::Amount(unit) => Self::Amount(Self::instantiate_with(unit.clone(), fresh)).into(),
+            Self::Currency(unit) => {
+                Self::Currency(Self::instantiate_with(unit.clone(), fresh)).into()
+            }
+            Self::Observable(k) => {
+                Self::Observable(Self::instantiate_with(k.clone(), fresh)).into()
+            }
+            Self::Rate(from, to) => Self::Rate(
+                Self::instantiate_with(from.clone(), fresh),
+                Self::instantiate_with(to.clone(), fresh),
+            )
+            .into(),
+            Self::State(unit) => Self::State(Self::instantiate_with(unit.clone(), fresh)).into(),
+            Self::Unresolved(_) => fresh.entry(kind.as_ref() as *const _).or_default().clone(),
+            _ => kind,
+        }
+    }
+
+    /// The distinct unresolved kinds within a kind, other than units.
+    pub fn unresolved(kind: Rc<Kind>) -> Vec<Rc<Kind>> {
+        let kind = Self::simplify(kind);
+
+        match kind.as_ref() {
+            Self::Abstraction(from, to) => {
+                let mut unresolved = Self::unresolved(from.clone());
+
+                for k in Self::unresolved(to.clone()) {
+                    if !unresolved.iter().any(|u| Rc::ptr_eq(u, &k)) {
+                        unresolved.push(k);
+                    }
+                }
+
+                unresolved
+            }
+            Self::Observable(k) => Self::unresolved(k.clone()),
+            Self::Unresolved(_) => vec![kind],
+            _ => Vec::new(),
+        }
+    }
+
     pub fn contains(this: Rc<Kind>, other: Rc<Kind>) -> bool {
         if Rc::ptr_eq(&this, &other) {
             return true;
@@ -43,10 +125,20 @@ impl Kind {
             Self::Abstraction(from, to) => {
                 Self::contains(from.clone(), other.clone()) || Self::contains(to.clone(), other)
             }
+            Self::Address => false,
+            Self::Amount(unit) | Self::Currency(unit) | Self::State(unit) => {
+                Self::contains(unit.clone(), other)
+            }
             Self::Boolean => false,
             Self::Date => false,
+            Self::Decimal => false,
+            Self::Duration => false,
             Self::Observable(k) => Self::contains(k.clone(), other),
-            Self::State => false,
+            Self::Rate(from, to) => {
+                Self::contains(from.clone(), other.clone()) || Self::contains(to.clone(), other)
+            }
+            Self::Role => false,
+            Self::Unit(_) => false,
             Self::Unresolved(k) => match k.borrow().as_ref() {
                 Some(k) => Self::contains(k.clone(), other),
                 None => false,
@@ -98,6 +190,15 @@ impl<'a> Formatter<'a> {
             }
         }
     }
+
+    // Currencies and contracts are written with their units only once those are known, as they
+    // are otherwise implicit.
+    fn resolved(&self, name: &str, unit: &'a Rc<Kind>, f: &mut fmt::Formatter) -> fmt::Result {
+        match Kind::simplify(unit.clone()).as_ref() {
+            Kind::Unresolved(_) => write!(f, "{}", name),
+            _ => write!(f, "{} {}", name, self.with(unit)),
+        }
+    }
 }
 
 impl Display for Formatter<'_> {
@@ -113,10 +214,27 @@ impl Display for Formatter<'_> {
 
                 write!(f, " -> {}", self.with(to))
             }
+            Kind::Address => write!(f, "Address"),
+            Kind::Amount(unit) => write!(f, "Amount {}", self.with(unit)),
             Kind::Boolean => write!(f, "Bool"),
+            Kind::Currency(unit) => self.resolved("Currency", unit, f),
             Kind::Date => write!(f, "Date"),
-            Kind::Observable(k) => write!(f, "Observable {}", self.with(k)),
-            Kind::State => write!(f, "Contract"),
+            Kind::Decimal => write!(f, "Decimal"),
+            Kind::Duration => write!(f, "Duration"),
+            Kind::Observable(k) => {
+                let k = Kind::simplify(k.clone());
+
+                match k.as_ref() {
+                    Kind::Amount(_) | Kind::Rate(_, _) => {
+                        write!(f, "Observable ({})", self.with(&k))
+                    }
+                    _ => write!(f, "Observable {}", self.with(&k)),
+                }
+            }
+            Kind::Rate(from, to) => write!(f, "Rate {} {}", self.with(from), self.with(to)),
+            Kind::Role => write!(f, "Role"),
+            Kind::State(unit) => self.resolved("Contract", unit, f),
+            Kind::Unit(name) => write!(f, "{}", name),
             Kind::Unresolved(k) => match k.borrow().as_ref() {
                 Some(k) => self.with(k).fmt(f),
                 None => write!(f, "{}", self.symbol()),
@@ -5,18 +5,28 @@ mod date;
 mod kind;
 
 pub use self::{
-    class::{Class, Comparable, Equatable, Negatable, Numerable},
-    date::Date,
+    class::{Boolean, Class, Comparable, Equatable, Negatable, Numerable},
+    date::{Date, Duration},
     kind::Kind,
     state::State,
 };
 
 use super::parser::Span;
 use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
     hash::{Hash, Hasher},
+    iter,
     rc::Rc,
 };
 
+// The names arguments are renamed to where they would capture a variable, which are each distinct
+// from any other name in scope when they are chosen.
+const FRESH_NAMES: [&str; 26] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s",
+    "t", "u", "v", "w", "x", "y", "z",
+];
+
 pub type Definitions<'a> = Vec<Definition<'a>>;
 
 #[derive(Clone, Debug)]
@@ -48,16 +58,201 @@ impl<'a> Expression<'a> {
     pub fn kind(&self) -> Rc<Kind> {
         self.expression.kind()
     }
+
+    /// The immediate subexpressions, including those of states and classes.
+    pub fn children(&self) -> Vec<&Expression<'a>> {
+        match &self.expression {
+            ExpressionType::Abstraction(_, e) => vec![e],
+            ExpressionType::Amount(_, e) => vec![e],
+            ExpressionType::Application(f, a) => vec![f, a],
+            ExpressionType::Class(c) => c.operands(),
+            ExpressionType::Date(Date::Horizon(e)) => vec![e],
+            ExpressionType::Date(Date::Earliest(dates))
+            | ExpressionType::Date(Date::Latest(dates)) => dates.iter().collect(),
+            ExpressionType::Observable(o) => o.operands(),
+            ExpressionType::State(s) => s
+                .transitions()
+                .iter()
+                .flat_map(|transition| {
+                    let effects =
+                        transition
+                            .effects()
+                            .into_iter()
+                            .filter_map(|effect| match effect {
+                                state::Effect::GiveTo(e)
+                                | state::Effect::Guard(e)
+                                | state::Effect::PayTo(e)
+                                | state::Effect::Scale(e)
+                                | state::Effect::Spawn(e)
+                                | state::Effect::Withdraw(e) => Some(e),
+                                state::Effect::Flip => None,
+                            });
+
+                    transition
+                        .conditions()
+                        .iter()
+                        .chain(effects)
+                        .chain(std::iter::once(transition.next()))
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Rebuilds the expression with its immediate subexpressions mapped by a function.
+    pub fn map(&self, f: impl Fn(&Expression<'a>) -> Expression<'a>) -> Self {
+        let expression = match &self.expression {
+            ExpressionType::Abstraction(argument, e) => {
+                ExpressionType::Abstraction(argument.clone(), f(e).into())
+            }
+            ExpressionType::Amount(unit, e) => ExpressionType::Amount(unit.clone(), f(e).into()),
+            ExpressionType::Application(e, a) => {
+                ExpressionType::Application(f(e).into(), f(a).into())
+            }
+            ExpressionType::Class(c) => ExpressionType::Class(c.map(f)),
+            ExpressionType::Date(Date::Horizon(e)) => {
+                ExpressionType::Date(Date::Horizon(f(e).into()))
+            }
+            ExpressionType::Date(Date::Earliest(dates)) => {
+                ExpressionType::Date(Date::Earliest(dates.iter().map(&f).collect()))
+            }
+            ExpressionType::Date(Date::Latest(dates)) => {
+                ExpressionType::Date(Date::Latest(dates.iter().map(&f).collect()))
+            }
+            ExpressionType::Observable(o) => ExpressionType::Observable(o.map(f)),
+            ExpressionType::State(s) => ExpressionType::State(s.map(f)),
+            e => e.clone(),
+        };
+
+        Self::new(expression, self.span)
+    }
+
+    /// Replaces the free occurrences of variables with expressions, all at once so that the
+    /// replacements are not themselves substituted. Arguments which would capture variables free
+    /// in the replacements are renamed.
+    pub fn substitute(&self, substitutions: &HashMap<&str, Expression<'a>>) -> Self {
+        match &self.expression {
+            ExpressionType::Abstraction(argument, e) => {
+                let mut substitutions = substitutions.clone();
+                substitutions.remove(argument.name);
+
+                let captured: HashSet<_> = e
+                    .free_variables()
+                    .into_iter()
+                    .filter_map(|variable| substitutions.get(variable.name))
+                    .flat_map(Expression::free_variables)
+                    .map(|variable| variable.name)
+                    .collect();
+
+                let (argument, e) = match captured.contains(argument.name) {
+                    true => {
+                        let mut used = captured;
+                        e.names(&mut used);
+
+                        let name = FRESH_NAMES
+                            .iter()
+                            .copied()
+                            .find(|name| !used.contains(name))
+                            .expect("there are more fresh names than variables in scope");
+                        let names = iter::once((argument.name, name)).collect();
+
+                        (
+                            Variable::new(name, argument.kind.clone(), argument.span),
+                            e.rename(&names),
+                        )
+                    }
+                    false => (argument.clone(), e.as_ref().clone()),
+                };
+
+                Self::new(
+                    ExpressionType::Abstraction(argument, e.substitute(&substitutions).into()),
+                    self.span,
+                )
+            }
+            ExpressionType::Variable(v) => substitutions
+                .get(v.name)
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            _ => self.map(|e| e.substitute(substitutions)),
+        }
+    }
+
+    // The names of the variables and arguments within an expression, whether free or not.
+    fn names(&self, names: &mut HashSet<&'a str>) {
+        match &self.expression {
+            ExpressionType::Abstraction(argument, _) => {
+                names.insert(argument.name);
+            }
+            ExpressionType::Variable(v) => {
+                names.insert(v.name);
+            }
+            _ => {}
+        }
+
+        for child in self.children() {
+            child.names(names);
+        }
+    }
+
+    /// Renames the free occurrences of variables, keeping their kinds and spans.
+    pub fn rename(&self, names: &HashMap<&str, &'a str>) -> Self {
+        match &self.expression {
+            ExpressionType::Abstraction(argument, e) if names.contains_key(argument.name) => {
+                let mut names = names.clone();
+                names.remove(argument.name);
+
+                Self::new(
+                    ExpressionType::Abstraction(argument.clone(), e.rename(&names).into()),
+                    self.span,
+                )
+            }
+            ExpressionType::Variable(v) => match names.get(v.name) {
+                Some(name) => Self::new(
+                    ExpressionType::Variable(Variable::new(name, v.kind.clone(), v.span)),
+                    self.span,
+                ),
+                None => self.clone(),
+            },
+            _ => self.map(|e| e.rename(names)),
+        }
+    }
+
+    /// The occurrences of variables which are not bound by an abstraction in the expression.
+    pub fn free_variables(&self) -> Vec<&Variable<'a>> {
+        match &self.expression {
+            ExpressionType::Abstraction(argument, e) => e
+                .free_variables()
+                .into_iter()
+                .filter(|variable| variable.name != argument.name)
+                .collect(),
+            ExpressionType::Variable(v) => vec![v],
+            _ => self
+                .children()
+                .into_iter()
+                .flat_map(Expression::free_variables)
+                .collect(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum ExpressionType<'a> {
     Abstraction(Variable<'a>, Box<Expression<'a>>),
+    /// An address, by its hexadecimal digits as written, e.g. `0x2a`.
+    Address(&'a str),
+    /// A decimal as an amount of a unit, e.g. `amount "USD" 100.0`.
+    Amount(Rc<Kind>, Box<Expression<'a>>),
     Application(Box<Expression<'a>>, Box<Expression<'a>>),
     Boolean(bool),
     Class(Class<'a>),
-    Date(Date),
+    /// A currency, by its name, e.g. `"USD"`.
+    Currency(&'a str),
+    Date(Date<'a>),
+    Decimal(Decimal),
+    Duration(Duration),
     Observable(Observable<'a>),
+    /// A role, by its position among the roles declared by the program and its name.
+    Role(u64, &'a str),
     State(State<'a>),
     Variable(Variable<'a>),
     Word(u64),
@@ -68,30 +263,56 @@ impl ExpressionType<'_> {
         match self {
             Self::Abstraction(from, to) => Kind::Abstraction(from.kind.clone(), to.kind()).into(),
 
-            Self::Application(f, _) => match f.kind().as_ref() {
+            Self::Address(_) => Kind::Address.into(),
+
+            Self::Amount(unit, _) => Kind::Amount(unit.clone()).into(),
+
+            Self::Application(f, _) => match Kind::simplify(f.kind()).as_ref() {
                 Kind::Abstraction(_, k) => k.clone(),
                 _ => unreachable!(),
             },
 
             Self::Boolean(_) => Kind::Boolean.into(),
 
-            Self::Class(c) => match c {
-                Class::Comparable(_) => Kind::Boolean.into(),
-                Class::Equatable(_) => Kind::Boolean.into(),
-                Class::Negatable(Negatable::Negate(e)) => e.kind(),
-                Class::Numerable(n) => n.kind(),
-            },
+            Self::Class(c) => c.kind(),
 
-            Self::Date(_) => Kind::Date.into(),
+            Self::Currency(name) => Kind::Currency(Kind::Unit(name.to_string()).into()).into(),
 
-            Self::Observable(o) => Kind::Observable(match o {
-                Observable::IsParty => Kind::Boolean.into(),
-                Observable::IsCounterparty => Kind::Boolean.into(),
-                Observable::Konst(e) => e.kind(),
-            })
-            .into(),
+            Self::Date(_) => Kind::Date.into(),
 
-            Self::State(_) => Kind::State.into(),
+            Self::Decimal(_) => Kind::Decimal.into(),
+
+            Self::Duration(_) => Kind::Duration.into(),
+
+            Self::Observable(o) => {
+                Kind::Observable(match o {
+                    Observable::IsParty => Kind::Boolean.into(),
+                    Observable::IsCounterparty => Kind::Boolean.into(),
+                    Observable::IsRole(_) => Kind::Boolean.into(),
+                    Observable::Konst(e) => e.kind(),
+                    Observable::Exchange(from, to) => Kind::Rate(
+                        Kind::Unit(from.to_string()).into(),
+                        Kind::Unit(to.to_string()).into(),
+                    )
+                    .into(),
+                    Observable::Fixed(_) => Kind::Decimal.into(),
+                    Observable::Lift(f, arguments) => arguments.iter().fold(f.kind(), |kind, _| {
+                        match Kind::simplify(kind).as_ref() {
+                            Kind::Abstraction(_, k) => k.clone(),
+                            _ => unreachable!(),
+                        }
+                    }),
+                    Observable::Named(_)
+                    | Observable::Max(_, _)
+                    | Observable::Min(_, _)
+                    | Observable::Observed(_, _, _) => Kind::Word.into(),
+                })
+                .into()
+            }
+
+            Self::Role(_, _) => Kind::Role.into(),
+
+            Self::State(s) => Kind::State(s.unit()).into(),
 
             Self::Variable(v) => v.kind.clone(),
 
@@ -112,6 +333,12 @@ impl<'a> From<Class<'a>> for ExpressionType<'a> {
     }
 }
 
+impl From<Decimal> for ExpressionType<'_> {
+    fn from(d: Decimal) -> Self {
+        Self::Decimal(d)
+    }
+}
+
 impl<'a> From<Observable<'a>> for ExpressionType<'a> {
     fn from(o: Observable<'a>) -> Self {
         Self::Observable(o)
@@ -136,6 +363,27 @@ impl From<u64> for ExpressionType<'_> {
     }
 }
 
+/// A decimal literal, as its digits and the number of them after the decimal point, so `0.025` is
+/// 25 with 3 places.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Decimal {
+    pub digits: u64,
+    pub places: u32,
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.places == 0 {
+            return write!(f, "{}", self.digits);
+        }
+
+        let digits = format!("{:0>width$}", self.digits, width = self.places as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - self.places as usize);
+
+        write!(f, "{}.{}", whole, fraction)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Variable<'a> {
     pub name: &'a str,
@@ -167,7 +415,89 @@ impl Hash for Variable<'_> {
 pub enum Observable<'a> {
     IsParty,
     IsCounterparty,
+    /// Whether the sender of a transition holds a role, e.g. `isRole agent`.
+    IsRole(Rc<Expression<'a>>),
     Konst(Rc<Expression<'a>>),
+    /// An observable word, decimal or amount as a decimal, which is how contracts are scaled.
+    Fixed(Rc<Expression<'a>>),
+    /// An observable word provided by the oracle configured for its name, e.g. `obs "USD/EUR"`.
+    Named(&'a str),
+    /// The rate at which amounts of a currency exchange for amounts of another, provided by the
+    /// oracle configured for the name of the pair, e.g. `fx "USD" "EUR"` by that for `USD/EUR`.
+    Exchange(&'a str, &'a str),
+    /// A function applied to the values of observables.
+    Lift(Rc<Expression<'a>>, Vec<Rc<Expression<'a>>>),
+    Max(Rc<Expression<'a>>, Rc<Expression<'a>>),
+    Min(Rc<Expression<'a>>, Rc<Expression<'a>>),
+    /// A statistic of the values of an observable word recorded on a schedule, e.g.
+    /// `average 2021-01-01T00:00:00Z 1m 12 (obs "USD/EUR")`.
+    Observed(Statistic, Schedule, Rc<Expression<'a>>),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Statistic {
+    Average,
+    Maximum,
+    Minimum,
+}
+
+/// Observation dates, from the first one and each a whole number of periods after it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Schedule {
+    pub start: u64,
+    pub period: Duration,
+    pub count: u64,
+}
+
+impl Schedule {
+    /// The date the last observation can be recorded until, which is checked to be in range when
+    /// the schedule is parsed.
+    pub fn end(&self) -> u64 {
+        self.period
+            .times(self.count)
+            .and_then(|duration| duration.after(self.start))
+            .unwrap()
+    }
+}
+
+impl<'a> Observable<'a> {
+    pub fn operands(&self) -> Vec<&Expression<'a>> {
+        match self {
+            Self::IsParty | Self::IsCounterparty | Self::Named(_) | Self::Exchange(_, _) => {
+                Vec::new()
+            }
+            Self::IsRole(e) | Self::Konst(e) | Self::Fixed(e) | Self::Observed(_, _, e) => {
+                vec![e]
+            }
+            Self::Lift(f, arguments) => std::iter::once(f)
+                .chain(arguments)
+                .map(AsRef::as_ref)
+                .collect(),
+            Self::Max(left, right) | Self::Min(left, right) => vec![left, right],
+        }
+    }
+
+    /// Rebuilds the observable with its operands mapped by a function.
+    pub fn map(&self, f: impl Fn(&Expression<'a>) -> Expression<'a>) -> Self {
+        match self {
+            Self::IsParty => Self::IsParty,
+            Self::IsCounterparty => Self::IsCounterparty,
+            Self::Named(name) => Self::Named(name),
+            Self::Exchange(from, to) => Self::Exchange(from, to),
+            Self::IsRole(e) => Self::IsRole(f(e).into()),
+            Self::Konst(e) => Self::Konst(f(e).into()),
+            Self::Fixed(e) => Self::Fixed(f(e).into()),
+            Self::Lift(function, arguments) => Self::Lift(
+                f(function).into(),
+                arguments.iter().map(|a| f(a).into()).collect(),
+            ),
+            Self::Max(left, right) => Self::Max(f(left).into(), f(right).into()),
+            Self::Min(left, right) => Self::Min(f(left).into(), f(right).into()),
+            Self::Observed(statistic, schedule, e) => {
+                Self::Observed(*statistic, *schedule, f(e).into())
+            }
+        }
+    }
 }
 
 impl<'a> From<Expression<'a>> for Observable<'a> {
@@ -226,4 +556,73 @@ mod tests {
         // three :: Boolean
         assert_eq!(*three.kind(), Kind::Boolean);
     }
+
+    #[test]
+    fn duration_after_month_ends() {
+        let months = |months| Duration { months, seconds: 0 };
+
+        // 2020-01-31 + 1m is 2020-02-29, in a leap year, and 2019-01-31 + 1m is 2019-02-28
+        assert_eq!(months(1).after(1_580_428_800), Some(1_582_934_400));
+        assert_eq!(months(1).after(1_548_892_800), Some(1_551_312_000));
+
+        // 2020-02-29 + 1y is 2021-02-28, while 2020-02-29 + 4y is 2024-02-29
+        assert_eq!(months(12).after(1_582_934_400), Some(1_614_470_400));
+        assert_eq!(months(48).after(1_582_934_400), Some(1_709_164_800));
+
+        // 2020-03-31 - 1m is 2020-02-29, and 2020-12-31 + 1m is 2021-01-31
+        assert_eq!(months(1).before(1_585_612_800), Some(1_582_934_400));
+        assert_eq!(months(1).after(1_609_372_800), Some(1_612_051_200));
+
+        // 2020-01-31 + (1m + 1d) is 2020-03-01, as months are added first, and is reversed by
+        // subtracting the day first, giving 2020-01-29
+        let month_and_day = Duration {
+            months: 1,
+            seconds: 86400,
+        };
+        assert_eq!(month_and_day.after(1_580_428_800), Some(1_583_020_800));
+        assert_eq!(month_and_day.before(1_583_020_800), Some(1_580_256_000));
+
+        assert_eq!(months(1).before(0), None);
+        assert_eq!(months(1).word(), Some(Duration::MONTH));
+        assert_eq!(month_and_day.times(1 << 24), None);
+
+        // Three monthly observations from 2020-01-31 can be recorded until 2020-04-30, as each
+        // date is counted from the start, rather than 2020-04-29 by adding a month three times
+        let schedule = Schedule {
+            start: 1_580_428_800,
+            period: months(1),
+            count: 3,
+        };
+        assert_eq!(schedule.end(), 1_588_204_800);
+    }
+
+    #[test]
+    fn substitute_without_capture() {
+        let variable = |name| {
+            Expression::new(
+                ExpressionType::from(Variable::new(name, Kind::Word.into(), None)),
+                None,
+            )
+        };
+
+        // \y -> x
+        let abstraction = Expression::new(
+            ExpressionType::Abstraction(
+                Variable::new("y", Kind::Word.into(), None),
+                variable("x").into(),
+            ),
+            None,
+        );
+
+        // Substituting y for x renames the argument, rather than giving \y -> y
+        let substitutions = iter::once(("x", variable("y"))).collect();
+
+        match abstraction.substitute(&substitutions).expression {
+            ExpressionType::Abstraction(argument, e) => {
+                assert_ne!(argument.name, "y");
+                assert!(matches!(&e.expression, ExpressionType::Variable(v) if v.name == "y"));
+            }
+            _ => unreachable!(),
+        }
+    }
 }
@@ -1,11 +1,25 @@
-use super::{Expression, ExpressionType};
+use super::{Class, Comparable, Date, Expression, ExpressionType, Kind};
+use std::{iter, rc::Rc};
 
 #[derive(Default, Clone, Debug)]
 pub struct State<'a> {
     transitions: Vec<Transition<'a>>,
+    unit: Rc<Kind>,
 }
 
 impl<'a> State<'a> {
+    /// A state paying in a unit, which is shared with the contracts it continues as.
+    pub fn with_unit(unit: Rc<Kind>) -> Self {
+        Self {
+            transitions: Vec::new(),
+            unit,
+        }
+    }
+
+    pub fn unit(&self) -> Rc<Kind> {
+        self.unit.clone()
+    }
+
     pub fn transitions(&self) -> &[Transition<'a>] {
         &self.transitions
     }
@@ -18,6 +32,46 @@ impl<'a> State<'a> {
     pub fn is_terminal(&self) -> bool {
         self.transitions.is_empty()
     }
+
+    /// The dates after which none of the transitions can be taken, the latest of which is the
+    /// horizon of the state, if it has one. Terminal states have no horizon.
+    pub fn horizon(&self) -> Option<Vec<Expression<'a>>> {
+        let horizons: Option<Vec<_>> = self.transitions.iter().map(Transition::horizon).collect();
+
+        horizons
+            .filter(|horizons| !horizons.is_empty())
+            .map(|horizons| horizons.into_iter().flatten().collect())
+    }
+
+    /// Rebuilds the state with each expression of its transitions mapped.
+    pub fn map(&self, f: impl Fn(&Expression<'a>) -> Expression<'a>) -> Self {
+        let transitions = self
+            .transitions
+            .iter()
+            .map(|transition| Transition {
+                conditions: transition.conditions.iter().map(&f).collect(),
+                effects: transition
+                    .effects
+                    .iter()
+                    .map(|effect| match effect {
+                        Effect::Flip => Effect::Flip,
+                        Effect::GiveTo(e) => Effect::GiveTo(f(e)),
+                        Effect::Guard(e) => Effect::Guard(f(e)),
+                        Effect::PayTo(e) => Effect::PayTo(f(e)),
+                        Effect::Scale(e) => Effect::Scale(f(e)),
+                        Effect::Spawn(e) => Effect::Spawn(f(e)),
+                        Effect::Withdraw(e) => Effect::Withdraw(f(e)),
+                    })
+                    .collect(),
+                next: f(&transition.next),
+            })
+            .collect();
+
+        Self {
+            transitions,
+            unit: self.unit.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -42,6 +96,48 @@ impl<'a> Transition<'a> {
         &self.conditions
     }
 
+    /// The dates after which the transition can no longer be taken, the latest of which is its
+    /// horizon, if it has one. A transition lasts as long as the next contract and those it
+    /// spawns, or until a date of its own if that is earlier.
+    pub fn horizon(&self) -> Option<Vec<Expression<'a>>> {
+        let date = self.conditions.iter().find_map(|condition| {
+            let (left, right) = match &condition.expression {
+                ExpressionType::Class(Class::Comparable(Comparable::Less(left, right))) => {
+                    (left, right)
+                }
+                ExpressionType::Class(Class::Comparable(Comparable::LessEqual(left, right))) => {
+                    (left, right)
+                }
+                _ => return None,
+            };
+
+            match left.expression {
+                ExpressionType::Date(Date::Now) => Some(right.as_ref()),
+                _ => None,
+            }
+        });
+
+        let spawned = self.effects.iter().filter_map(|effect| match effect {
+            Effect::Spawn(contract) => Some(contract),
+            _ => None,
+        });
+
+        let horizons: Option<Vec<_>> = iter::once(&self.next).chain(spawned).map(horizon).collect();
+        let horizons = horizons.map(|horizons| horizons.into_iter().flatten().collect::<Vec<_>>());
+
+        match (date, horizons) {
+            (Some(date), Some(horizons)) if !horizons.is_empty() => Some(vec![Expression::new(
+                ExpressionType::Date(Date::Earliest(vec![
+                    date.clone(),
+                    Expression::new(ExpressionType::Date(Date::Latest(horizons)), None),
+                ])),
+                None,
+            )]),
+            (Some(date), _) => Some(vec![date.clone()]),
+            (None, horizons) => horizons,
+        }
+    }
+
     pub fn add_condition(&mut self, condition: Expression<'a>) -> &mut Self {
         self.conditions.push(condition);
         self
@@ -69,7 +165,26 @@ impl<'a> Transition<'a> {
 #[derive(Clone, Debug)]
 pub enum Effect<'a> {
     Flip,
+    /// Makes a role the party, and the party the counterparty.
+    GiveTo(Expression<'a>),
+    /// Abandons the contract once an observable boolean is true, whichever state it is in.
+    Guard(Expression<'a>),
+    /// Routes the payments of the contract to an address, rather than to its party.
+    PayTo(Expression<'a>),
     Scale(Expression<'a>),
     Spawn(Expression<'a>),
-    Withdraw,
+    Withdraw(Expression<'a>),
+}
+
+// The horizon of a contract is only known before it is generated where it is a state, and is
+// otherwise taken of the contract the argument is when the transition is run.
+fn horizon<'a>(contract: &Expression<'a>) -> Option<Vec<Expression<'a>>> {
+    match &contract.expression {
+        ExpressionType::State(state) => state.horizon(),
+        ExpressionType::Variable(_) => Some(vec![Expression::new(
+            ExpressionType::Date(Date::Horizon(contract.clone().into())),
+            None,
+        )]),
+        _ => None,
+    }
 }
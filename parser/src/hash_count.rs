@@ -19,11 +19,6 @@ impl<T: Eq + Hash> HashCount<T> {
         self.0.insert(value, count + 1);
     }
 
-    pub fn insert_without_increment(&mut self, value: T) {
-        let count = self.count(&value);
-        self.0.insert(value, count);
-    }
-
     pub fn take(&mut self, value: &T) -> Option<T> {
         self.0.remove_entry(value).map(|(value, _)| value)
     }
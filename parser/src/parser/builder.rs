@@ -1,73 +1,476 @@
 use super::{
-    context::Context,
+    context::{Context, Signature},
     error::SprintError,
+    module::{Import, Module},
     primitive::{self, PRIMITIVES},
     unify::Unify,
-    Error, Result, Span,
+    Arena, Error, Result, Span,
+};
+use crate::ast::{
+    state::Effect, Boolean, Class, Comparable, Definition, Equatable, Expression, ExpressionType,
+    Kind, Negatable, Numerable, Observable, Variable,
 };
-use crate::ast::{Definition, Expression, ExpressionType, Kind, Variable};
 use nom::Err;
-use std::rc::Rc;
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+    rc::Rc,
+    result,
+};
 
-pub fn program<'a>(definitions: Vec<Context<'a, Expression<'a>>>) -> Result<'a, Context<'a, ()>> {
+pub fn module<'a>(
+    name: Option<Span<'a>>,
+    exports: Option<Vec<Span<'a>>>,
+    imports: Vec<Import<'a>>,
+    definitions: Vec<Context<'a, Expression<'a>>>,
+) -> Result<'a, Module<'a>> {
+    let context = definitions
+        .into_iter()
+        .fold(Ok(Context::from(())), unify_context)?;
+
+    Ok(Module::new(name, exports, imports, context))
+}
+
+pub fn program<'a>(
+    prelude: Module<'a>,
+    modules: Vec<Module<'a>>,
+    arena: &'a Arena,
+) -> Result<'a, Context<'a, ()>> {
     let mut context = Context::from(());
 
     context.unify(primitive::zero()).unwrap();
     context.unify(primitive::one()).unwrap();
     context.unify(primitive::give()).unwrap();
+    context.unify(primitive::give_to()).unwrap();
+    context.unify(primitive::pay_to()).unwrap();
     context.unify(primitive::and()).unwrap();
     context.unify(primitive::or()).unwrap();
+    context.unify(primitive::cond()).unwrap();
     context.unify(primitive::before()).unwrap();
     context.unify(primitive::after()).unwrap();
+    context.unify(primitive::truncate()).unwrap();
+    context.unify(primitive::then()).unwrap();
+    context.unify(primitive::when()).unwrap();
+    context.unify(primitive::until()).unwrap();
     context.unify(primitive::scale()).unwrap();
     context.unify(primitive::anytime()).unwrap();
 
-    context = definitions.into_iter().fold(Ok(context), unify_context)?;
-    context
-        .unify(signature(Span::new("main"), Kind::State).unwrap())
-        .map_err(Err::Error)?;
+    let primitives: Vec<_> = context.definitions.keys().copied().collect();
+    let prelude_name = prelude.name.map(|name| name.fragment);
+    let modules: Vec<_> = iter::once(prelude).chain(sort(modules)?).collect();
+    let mut exports = HashMap::new();
 
-    for (variable, _) in &context.variables {
-        if !context.definitions.contains_key(variable.name) {
-            if variable.name == "main" {
-                return Err(Err::Error(Error::from_sprint_error(
-                    SprintError::UndefinedMainError,
-                    None,
-                )));
-            }
+    // Each module has its own namespace, so the definitions of the prelude and imported modules
+    // are qualified to keep them apart from those of other modules.
+    for (i, module) in modules.iter().enumerate() {
+        let qualifier = match i + 1 == modules.len() {
+            true => None,
+            false => module.name,
+        };
+
+        let prelude = prelude_name.and_then(|name| exports.get(name));
+        let names = namespace(module, qualifier, &exports, prelude, arena)?;
+
+        let unknown = (&module.context.variables)
+            .into_iter()
+            .map(|(variable, _)| variable)
+            .find(|variable| {
+                !names.contains_key(variable.name) && !primitives.contains(&variable.name)
+            });
+
+        if let Some(variable) = unknown {
             return Err(Err::Error(Error::from_sprint_error(
                 SprintError::UnknownIdentifierError(
                     variable.name,
-                    Rc::make_mut(&mut variable.kind.clone()).clone(),
+                    Kind::simplify(variable.kind.clone()).as_ref().clone(),
                 ),
                 variable.span,
             )));
         }
+
+        if let Some(name) = module.name {
+            exports.insert(name.fragment, module_exports(module, &names)?);
+        }
+
+        context
+            .unify(rename(module.context.clone(), &names))
+            .map_err(Err::Error)?;
+    }
+
+    let scales = aliases(&context, "scale");
+
+    for definition in context.definitions.values_mut() {
+        definition.expression = scalars(&definition.expression, &scales);
+    }
+
+    // The roles of a program are declared by at most one of its modules, as their positions are
+    // those of the addresses a contract is initialized with.
+    let mut roles = HashSet::new();
+
+    for definition in context.definitions.values() {
+        if let ExpressionType::Role(index, _) = definition.expression.expression {
+            if !roles.insert(index) {
+                return Err(Err::Error(Error::from_sprint_error(
+                    SprintError::InvalidRolesError,
+                    definition.expression.span,
+                )));
+            }
+        }
+    }
+
+    // `main` is a contract, which may take arguments that are given when it is initialized.
+    let main = Rc::new(Kind::State(Kind::default().into()));
+
+    match context.definitions.get("main") {
+        Some(definition) if !arguments(&definition.expression).0.is_empty() => {
+            let (_, body) = arguments(&definition.expression);
+
+            main.unify(body.kind()).map_err(|e| {
+                let sprint_error = SprintError::TypeError("main", e.sprint_error.unwrap().into());
+                Err::Error(Error::from_sprint_error(sprint_error, body.span))
+            })?;
+        }
+        _ => context
+            .unify(signature(Span::new("main"), main).unwrap())
+            .map_err(Err::Error)?,
+    }
+
+    if !context.definitions.contains_key("main") {
+        return Err(Err::Error(Error::from_sprint_error(
+            SprintError::UndefinedMainError,
+            None,
+        )));
+    }
+
+    let undefined = (&context.variables)
+        .into_iter()
+        .map(|(variable, _)| variable)
+        .chain(context.signatures.values().map(|s| &s.variable))
+        .find(|variable| !context.definitions.contains_key(variable.name));
+
+    if let Some(variable) = undefined {
+        return Err(Err::Error(Error::from_sprint_error(
+            SprintError::UnknownIdentifierError(
+                variable.name,
+                Kind::simplify(variable.kind.clone()).as_ref().clone(),
+            ),
+            variable.span,
+        )));
+    }
+
+    infer(&context)?;
+
+    for (name, definition) in &context.definitions {
+        instances(&definition.expression)?;
+        durations(name, &definition.expression)?;
+    }
+
+    // The arguments of `main` are pushed onto the stack of the contract, so they must be words or
+    // kinds represented by them.
+    for argument in arguments(&context.definitions["main"].expression).0 {
+        let kind = Kind::simplify(argument.kind.clone());
+
+        match kind.as_ref() {
+            Kind::Amount(_)
+            | Kind::Boolean
+            | Kind::Date
+            | Kind::Decimal
+            | Kind::Duration
+            | Kind::Unresolved(_)
+            | Kind::Word => {}
+            _ => {
+                return Err(Err::Error(Error::from_sprint_error(
+                    SprintError::InvalidArgumentError(argument.name, kind.as_ref().clone()),
+                    argument.span,
+                )));
+            }
+        }
     }
 
     Ok(context)
 }
 
-pub fn signature(identifier: Span, kind: Kind) -> Result<Context<Expression>> {
-    let variable = Variable::new(identifier.fragment, kind.into(), Some(identifier));
+// The arguments a definition is abstracted over in order, and the expression it abstracts.
+fn arguments<'a, 'b>(
+    mut expression: &'b Expression<'a>,
+) -> (Vec<&'b Variable<'a>>, &'b Expression<'a>) {
+    let mut arguments = Vec::new();
+
+    while let ExpressionType::Abstraction(argument, e) = &expression.expression {
+        arguments.push(argument);
+        expression = e;
+    }
+
+    (arguments, expression)
+}
+
+// Orders modules after those they import, ending with the main module which is given first.
+fn sort<'a>(modules: Vec<Module<'a>>) -> Result<'a, Vec<Module<'a>>> {
+    let mut modules = modules.into_iter();
+
+    let main = modules.next().ok_or_else(|| {
+        Err::Error(Error::from_sprint_error(
+            SprintError::UndefinedMainError,
+            None,
+        ))
+    })?;
+
+    let mut modules = modules
+        .filter_map(|module| module.name.map(|name| (name.fragment, module)))
+        .collect();
+
+    let mut sorted = Vec::new();
+    sort_module(main, &mut modules, &mut Vec::new(), &mut sorted)?;
+
+    Ok(sorted)
+}
+
+fn sort_module<'a>(
+    module: Module<'a>,
+    modules: &mut HashMap<&'a str, Module<'a>>,
+    importing: &mut Vec<&'a str>,
+    sorted: &mut Vec<Module<'a>>,
+) -> Result<'a, ()> {
+    let name = module.name.map(|name| name.fragment);
+    importing.extend(name);
+
+    for import in &module.imports {
+        let name = import.module.fragment;
+
+        if importing.contains(&name) {
+            return Err(Err::Error(Error::from_sprint_error(
+                SprintError::CyclicImportError(name),
+                Some(import.module),
+            )));
+        }
+
+        match modules.remove(name) {
+            Some(imported) => sort_module(imported, modules, importing, sorted)?,
+            None if sorted
+                .iter()
+                .any(|m| m.name.map(|n| n.fragment) == Some(name)) => {}
+            None => {
+                return Err(Err::Error(Error::from_sprint_error(
+                    SprintError::UnknownModuleError(name),
+                    Some(import.module),
+                )))
+            }
+        }
+    }
+
+    importing.truncate(importing.len() - name.iter().count());
+    sorted.push(module);
+
+    Ok(())
+}
+
+// The names visible within a module, mapped to the names of the definitions they refer to.
+fn namespace<'a>(
+    module: &Module<'a>,
+    qualifier: Option<Span<'a>>,
+    exports: &HashMap<&'a str, HashMap<&'a str, &'a str>>,
+    prelude: Option<&HashMap<&'a str, &'a str>>,
+    arena: &'a Arena,
+) -> Result<'a, HashMap<&'a str, &'a str>> {
+    let mut names: HashMap<_, _> = module
+        .context
+        .definitions
+        .keys()
+        .map(|&name| match qualifier {
+            Some(qualifier) => (name, qualify(qualifier.fragment, name, arena)),
+            None => (name, name),
+        })
+        .collect();
+
+    for import in &module.imports {
+        let exported = &exports[import.module.fragment];
+
+        let imported = match &import.names {
+            None => exported
+                .iter()
+                .map(|(&name, &to)| (name, to, import.module))
+                .collect(),
+            Some(names) => names
+                .iter()
+                .map(|name| match exported.get(name.fragment) {
+                    Some(&to) => Ok((name.fragment, to, *name)),
+                    None => Err(Err::Error(Error::from_sprint_error(
+                        SprintError::UnknownExportError(import.module.fragment, name.fragment),
+                        Some(*name),
+                    ))),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        // Definitions of the module hide those which are imported.
+        for (name, to, span) in imported {
+            if module.context.definitions.contains_key(name) {
+                continue;
+            }
+
+            if *names.entry(name).or_insert(to) != to {
+                return Err(Err::Error(Error::from_sprint_error(
+                    SprintError::DuplicateDefinitionError(name),
+                    Some(span),
+                )));
+            }
+        }
+    }
+
+    // The prelude is hidden by both the definitions of the module and those it imports.
+    for (&name, &to) in prelude.into_iter().flatten() {
+        names.entry(name).or_insert(to);
+    }
+
+    Ok(names)
+}
+
+fn module_exports<'a>(
+    module: &Module<'a>,
+    names: &HashMap<&'a str, &'a str>,
+) -> Result<'a, HashMap<&'a str, &'a str>> {
+    match &module.exports {
+        None => Ok(module
+            .context
+            .definitions
+            .keys()
+            .map(|name| (*name, names[name]))
+            .collect()),
+        Some(exports) => exports
+            .iter()
+            .map(|name| match names.get(name.fragment) {
+                Some(&to) => Ok((name.fragment, to)),
+                None => Err(Err::Error(Error::from_sprint_error(
+                    SprintError::UnknownIdentifierError(name.fragment, Kind::default()),
+                    Some(*name),
+                ))),
+            })
+            .collect(),
+    }
+}
+
+fn qualify<'a>(module: &str, name: &str, arena: &'a Arena) -> &'a str {
+    arena.alloc_str(&format!("{}_{}", module.replace('.', "_"), name))
+}
+
+fn rename<'a>(context: Context<'a, ()>, names: &HashMap<&'a str, &'a str>) -> Context<'a, ()> {
+    let rename_variable = |variable: Variable<'a>| Variable {
+        name: names.get(variable.name).copied().unwrap_or(variable.name),
+        ..variable
+    };
+
+    let mut renamed = Context::from(());
+
+    for (_, definition) in context.definitions {
+        let variable = rename_variable(definition.variable);
+        let expression = definition.expression.rename(names);
+        renamed
+            .definitions
+            .insert(variable.name, Definition::new(variable, expression));
+    }
+
+    for (_, signature) in context.signatures {
+        let variable = rename_variable(signature.variable);
+        renamed.signatures.insert(
+            variable.name,
+            Signature {
+                variable,
+                ..signature
+            },
+        );
+    }
+
+    for (variable, count) in context.variables {
+        renamed.variables.replace(rename_variable(variable), count);
+    }
+
+    renamed
+}
+
+/// Defines each of the roles of a program as itself, by its position among them.
+pub fn roles<'a>(
+    keyword: Span<'a>,
+    names: Vec<Span<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    if names.len() < 2 {
+        return Err(Err::Failure(Error::from_sprint_error(
+            SprintError::InvalidRolesError,
+            Some(keyword),
+        )));
+    }
+
+    let mut roles = names.into_iter().enumerate().map(|(index, name)| {
+        let role = Expression::new(
+            ExpressionType::Role(index as u64, name.fragment),
+            Some(name),
+        );
+        definition(name, Vec::new(), role.into())
+    });
+
+    let mut context = roles.next().unwrap()?;
+
+    for role in roles {
+        context.unify(role?).map_err(Err::Failure)?;
+    }
+
+    Ok(context)
+}
+
+pub fn signature(identifier: Span, kind: Rc<Kind>) -> Result<Context<Expression>> {
+    let variable = Variable::new(identifier.fragment, kind, Some(identifier));
 
     let mut context = Context::from(Expression::new(
         ExpressionType::Variable(variable.clone()),
         Some(identifier),
     ));
-    context.variables.insert_without_increment(variable);
+    context
+        .signatures
+        .insert(identifier.fragment, Signature::new(variable));
 
     Ok(context)
 }
 
 pub fn definition<'a>(
     identifier: Span<'a>,
+    arguments: Vec<Span<'a>>,
+    expression: Context<'a, Expression<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    let (expression, definition) = abstraction(arguments, expression)?.clear();
+    let variable = Variable::new(identifier.fragment, definition.kind(), Some(identifier));
+
+    let mut context = Context::from(Expression::new(
+        ExpressionType::Variable(variable.clone()),
+        Some(identifier),
+    ));
+
+    let definition = Definition::new(variable.clone(), definition);
+
+    context.definitions.insert(identifier.fragment, definition);
+    context.variables.insert(variable);
+
+    context.unify(expression).map_err(Err::Error)?;
+
+    Ok(context)
+}
+
+pub fn abstraction<'a>(
     arguments: Vec<Span<'a>>,
     mut expression: Context<'a, Expression<'a>>,
 ) -> Result<'a, Context<'a, Expression<'a>>> {
     for argument in arguments.iter().rev() {
         let argument = Variable::new(argument.fragment, Default::default(), Some(*argument));
-        let argument = expression.variables.take(&argument).unwrap_or(argument);
+        expression.variables.take(&argument);
+
+        // Arguments are not generalised, so all of their occurrences have the same kind.
+        for occurrence in expression.as_ref().free_variables() {
+            if occurrence.name == argument.name {
+                argument
+                    .kind
+                    .clone()
+                    .unify(occurrence.kind.clone())
+                    .map_err(|error| type_error(argument.name, error, occurrence.span))?;
+            }
+        }
 
         expression = expression.map(|expression| {
             Expression::new(
@@ -77,22 +480,39 @@ pub fn definition<'a>(
         });
     }
 
-    let (expression, definition) = expression.clear();
-    let variable = Variable::new(identifier.fragment, definition.kind(), Some(identifier));
+    Ok(expression)
+}
 
-    let mut context = Context::from(Expression::new(
-        ExpressionType::Variable(variable.clone()),
-        Some(identifier),
-    ));
+pub fn bindings<'a>(
+    bindings: Vec<(Span<'a>, Context<'a, Expression<'a>>)>,
+    expression: Context<'a, Expression<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    // Bindings are inlined from the last one, so that each can refer to those before it.
+    bindings
+        .into_iter()
+        .rev()
+        .try_fold(expression, |expression, (name, binding)| {
+            let (mut context, expression) = expression.clear();
+            let (other, binding) = binding.clear();
 
-    let definition = Definition::new(variable.clone(), definition);
+            let variable = Variable::new(name.fragment, Default::default(), Some(name));
+            context.variables.take(&variable);
 
-    context.definitions.insert(identifier.fragment, definition);
-    context.variables.insert(variable);
+            for occurrence in expression.free_variables() {
+                if occurrence.name == variable.name {
+                    binding
+                        .kind()
+                        .unify(occurrence.kind.clone())
+                        .map_err(|error| type_error(variable.name, error, occurrence.span))?;
+                }
+            }
 
-    context.unify(expression).map_err(Err::Error)?;
+            context.unify(other).map_err(Err::Error)?;
 
-    Ok(context)
+            let substitutions = iter::once((variable.name, binding)).collect();
+            let expression = expression.substitute(&substitutions);
+            Ok(context.map(|_| expression))
+        })
 }
 
 pub fn application<'a>(
@@ -101,10 +521,10 @@ pub fn application<'a>(
 ) -> Result<'a, Context<'a, Expression<'a>>> {
     let (contexts, arguments): (Vec<_>, Vec<_>) = arguments.into_iter().map(Context::clear).unzip();
     let mut context = match PRIMITIVES.get(identifier.fragment) {
-        // Applications to primitives are handled here. Primitives cannot be
-        // implemented as abstractions in the AST as some require polymorphic
-        // kinds which is not currently supported.
-        Some(primitive) => primitive(arguments),
+        // Applications to primitives are handled here. These primitives are
+        // inlined rather than implemented as abstractions in the AST, as the
+        // code generated for them depends on the kinds of their arguments.
+        Some(primitive) => primitive(arguments).map_err(|error| at(error, identifier))?,
         _ => {
             let kind = arguments
                 .iter()
@@ -132,6 +552,838 @@ pub fn application<'a>(
     Ok(context)
 }
 
+pub fn binary<'a>(
+    operator: Span<'a>,
+    left: Context<'a, Expression<'a>>,
+    right: Context<'a, Expression<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    let (mut context, left) = left.clear();
+    let (other, right) = right.clear();
+    context.unify(other).map_err(Err::Error)?;
+
+    // The operands of additions, subtractions and multiplications can be of different kinds, which
+    // are resolved once they are known, while currencies are compared whatever their units.
+    match operator.fragment {
+        "+" | "-" | "*" => {}
+        "==" | "/=" => any_unit(left.kind())
+            .unify(any_unit(right.kind()))
+            .map_err(|error| at(error, operator))?,
+        _ => left
+            .kind()
+            .unify(right.kind())
+            .map_err(|error| at(error, operator))?,
+    }
+
+    let (left, right) = (left.into(), right.into());
+    let class = match operator.fragment {
+        "+" => Class::Numerable(Numerable::Add(left, right, Kind::default().into())),
+        "-" => Class::Numerable(Numerable::Subtract(left, right, Kind::default().into())),
+        "*" => Class::Numerable(Numerable::Multiply(left, right, Kind::default().into())),
+        "/" => Class::Numerable(Numerable::Divide(left, right)),
+        "<" => Class::Comparable(Comparable::Less(left, right)),
+        "<=" => Class::Comparable(Comparable::LessEqual(left, right)),
+        ">" => Class::Comparable(Comparable::Greater(left, right)),
+        ">=" => Class::Comparable(Comparable::GreaterEqual(left, right)),
+        "==" => Class::Equatable(Equatable::Equal(left, right)),
+        "/=" => Class::Equatable(Equatable::NotEqual(left, right)),
+        "&&" => Class::Boolean(Boolean::And(left, right)),
+        "||" => Class::Boolean(Boolean::Or(left, right)),
+        _ => unreachable!("unknown operator {}", operator.fragment),
+    };
+
+    arithmetic(&class, false).map_err(|error| at(error, operator))?;
+
+    Ok(context.map(|_| Expression::new(class.into(), Some(operator))))
+}
+
+// The kind with the unit of a currency, observed or not, replaced by a fresh one.
+fn any_unit(kind: Rc<Kind>) -> Rc<Kind> {
+    let kind = Kind::simplify(kind);
+
+    match kind.as_ref() {
+        Kind::Observable(k) => Kind::Observable(any_unit(k.clone())).into(),
+        Kind::Currency(_) => Kind::Currency(Kind::default().into()).into(),
+        _ => kind,
+    }
+}
+
+pub fn negation<'a>(
+    operator: Span<'a>,
+    operand: Context<'a, Expression<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    let (context, operand) = operand.clear();
+    let class = Class::Negatable(Negatable::Negate(operand.into()));
+
+    Ok(context.map(|_| Expression::new(class.into(), Some(operator))))
+}
+
+// Arithmetic on word literals is folded into a literal spanning it, which is out of range if the
+// result is negative or doesn't fit in a word, rather than aborting once the contract is run.
+// Divisions by zero are left to abort.
+pub fn fold<'a>(
+    span: Span<'a>,
+    expression: Context<'a, Expression<'a>>,
+) -> Result<'a, Context<'a, Expression<'a>>> {
+    let (context, expression) = expression.clear();
+    let word = |expression: &Expression| match expression.expression {
+        ExpressionType::Word(w) => Some(w),
+        _ => None,
+    };
+    let words = |left: &Expression, right: &Expression| Some((word(left)?, word(right)?));
+
+    let folded = match &expression.expression {
+        ExpressionType::Class(Class::Negatable(Negatable::Negate(e))) => {
+            word(e).map(|w| 0u64.checked_sub(w))
+        }
+        ExpressionType::Class(Class::Numerable(n)) => match n {
+            Numerable::Add(l, r, _) => words(l, r).map(|(l, r)| l.checked_add(r)),
+            Numerable::Subtract(l, r, _) => words(l, r).map(|(l, r)| l.checked_sub(r)),
+            Numerable::Multiply(l, r, _) => words(l, r).map(|(l, r)| l.checked_mul(r)),
+            Numerable::Divide(l, r) => words(l, r)
+                .filter(|(_, r)| *r != 0)
+                .map(|(l, r)| Some(l / r)),
+        },
+        _ => None,
+    };
+
+    match folded {
+        Some(Some(word)) => Ok(context.map(|_| Expression::new(word.into(), Some(span)))),
+        Some(None) => Err(Err::Failure(Error::from_sprint_error(
+            SprintError::OutOfRangeError(span.fragment, Kind::Word),
+            Some(span),
+        ))),
+        None => Ok(context.map(|_| expression)),
+    }
+}
+
+// A class which a kind must have an instance of, with where the kind was required to have it.
+type Constraint<'a, 'b> = (&'b Class<'a>, Rc<Kind>, Option<Span<'a>>);
+
+// Infers the kinds of definitions, such that each use of a definition can be at a different
+// instance of its kind. Definitions are inferred after those they depend on, and definitions which
+// depend on each other are inferred together without being generalised. The classes which the
+// kinds a definition is generalised over must have instances of are checked at each use of it.
+fn infer<'a>(context: &Context<'a, ()>) -> Result<'a, ()> {
+    let occurrences: HashMap<&str, Vec<&Variable>> = context
+        .definitions
+        .iter()
+        .map(|(name, definition)| {
+            let occurrences = definition
+                .expression
+                .free_variables()
+                .into_iter()
+                .filter(|variable| context.definitions.contains_key(variable.name))
+                .collect();
+
+            (*name, occurrences)
+        })
+        .collect();
+
+    let mut constraints: HashMap<&str, Vec<Constraint>> = HashMap::new();
+    let combinators: HashSet<_> = ["and", "cond", "or", "then"]
+        .iter()
+        .flat_map(|primitive| aliases(context, primitive))
+        .collect();
+
+    for group in components(&occurrences) {
+        let mut pending = Vec::new();
+
+        for name in &group {
+            if let Some(signature) = context.signatures.get(name) {
+                signature
+                    .variable
+                    .kind
+                    .clone()
+                    .unify(context.definitions[name].variable.kind.clone())
+                    .map_err(|error| type_error(name, error, signature.variable.span))?;
+            }
+        }
+
+        for name in &group {
+            for occurrence in &occurrences[name] {
+                let kind = context.definitions[occurrence.name].variable.kind.clone();
+                let kind = if group.contains(&occurrence.name) {
+                    kind
+                } else {
+                    // The constraints of the definition are instantiated along with its kind, and
+                    // are then those of this use.
+                    let inherited = &constraints[occurrence.name];
+                    let kinds = iter::once(kind)
+                        .chain(inherited.iter().map(|(_, kind, _)| kind.clone()))
+                        .collect();
+                    let mut kinds = Kind::instantiate_all(kinds).into_iter();
+                    let kind = kinds.next().unwrap();
+
+                    for ((class, _, _), kind) in inherited.iter().zip(kinds) {
+                        pending.push((*class, kind, occurrence.span));
+                    }
+
+                    kind
+                };
+
+                kind.unify(occurrence.kind.clone())
+                    .map_err(|error| type_error(occurrence.name, error, occurrence.span))?;
+            }
+        }
+
+        let expressions: Vec<_> = group
+            .iter()
+            .map(|name| &context.definitions[name].expression)
+            .collect();
+        resolve(&expressions, &combinators)?;
+
+        for expression in &expressions {
+            classes(expression, &mut pending);
+        }
+
+        // Constraints on kinds which are still unresolved are left to the uses of the group.
+        let mut unresolved = Vec::new();
+
+        for (class, kind, span) in pending {
+            let kind = Kind::simplify(kind);
+            let operand = match kind.as_ref() {
+                Kind::Observable(k) => Kind::simplify(k.clone()),
+                _ => kind.clone(),
+            };
+
+            match operand.as_ref() {
+                Kind::Unresolved(_) => unresolved.push((class, kind, span)),
+                _ => instance(class, &kind, span)?,
+            }
+        }
+
+        for name in &group {
+            constraints.insert(name, unresolved.clone());
+        }
+
+        for name in &group {
+            if let Some(signature) = context.signatures.get(name) {
+                let mut parameters: Vec<_> = signature
+                    .parameters
+                    .iter()
+                    .map(|parameter| Kind::simplify(parameter.clone()))
+                    .collect();
+
+                let resolved = parameters
+                    .iter()
+                    .any(|parameter| !matches!(parameter.as_ref(), Kind::Unresolved(_)));
+                parameters.dedup_by(|a, b| Rc::ptr_eq(a, b));
+
+                if resolved || parameters.len() != signature.parameters.len() {
+                    let kind = Kind::simplify(signature.variable.kind.clone());
+
+                    return Err(Err::Failure(Error::from_sprint_error(
+                        SprintError::GeneralSignatureError(name, kind.as_ref().clone()),
+                        signature.variable.span,
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The classes of the operators within an expression, along with the kinds of their operands.
+fn classes<'a, 'b>(expression: &'b Expression<'a>, found: &mut Vec<Constraint<'a, 'b>>) {
+    if let ExpressionType::Class(class) = &expression.expression {
+        found.push((class, class.operand_kind(), expression.span));
+    }
+
+    for child in expression.children() {
+        classes(child, found);
+    }
+}
+
+// Splits definitions into strongly connected components of the graph of their uses, such that each
+// component comes after those it depends on.
+fn components<'a>(occurrences: &HashMap<&'a str, Vec<&Variable<'a>>>) -> Vec<Vec<&'a str>> {
+    struct Search<'a, 'b> {
+        occurrences: &'b HashMap<&'a str, Vec<&'b Variable<'a>>>,
+        indices: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    impl<'a> Search<'a, '_> {
+        fn visit(&mut self, name: &'a str) -> usize {
+            let index = self.indices.len();
+            self.indices.insert(name, index);
+            self.stack.push(name);
+
+            let mut low = index;
+
+            for occurrence in &self.occurrences[name] {
+                low = low.min(match self.indices.get(occurrence.name) {
+                    None => self.visit(occurrence.name),
+                    Some(_) if !self.stack.contains(&occurrence.name) => continue,
+                    Some(&index) => index,
+                });
+            }
+
+            if low == index {
+                let position = self.stack.iter().position(|n| *n == name).unwrap();
+                self.components.push(self.stack.split_off(position));
+            }
+
+            low
+        }
+    }
+
+    let mut names: Vec<_> = occurrences.keys().copied().collect();
+    names.sort();
+
+    let mut search = Search {
+        occurrences,
+        indices: HashMap::new(),
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+
+    for name in names {
+        if !search.indices.contains_key(name) {
+            search.visit(name);
+        }
+    }
+
+    search.components
+}
+
+// The names of a primitive and of the definitions which are the primitive itself under another
+// name, such as `f` given `f = scale`.
+fn aliases<'a>(context: &Context<'a, ()>, primitive: &'a str) -> HashSet<&'a str> {
+    let mut names = HashSet::new();
+    names.insert(primitive);
+
+    loop {
+        let found: Vec<_> = context
+            .definitions
+            .iter()
+            .filter(|(name, definition)| {
+                !names.contains(*name)
+                    && matches!(
+                        &definition.expression.expression,
+                        ExpressionType::Variable(v) if names.contains(v.name)
+                    )
+            })
+            .map(|(name, _)| *name)
+            .collect();
+
+        if found.is_empty() {
+            return names;
+        }
+
+        names.extend(found);
+    }
+}
+
+// Contracts are scaled by decimals, to which whatever the `scale` primitive is applied to is
+// converted, so that contracts can be scaled by words, decimals or amounts. The primitive is known
+// by the names of its aliases, other than where they are bound to something else.
+fn scalars<'a>(expression: &Expression<'a>, scales: &HashSet<&'a str>) -> Expression<'a> {
+    match &expression.expression {
+        ExpressionType::Abstraction(argument, e) if scales.contains(argument.name) => {
+            let mut scales = scales.clone();
+            scales.remove(argument.name);
+
+            Expression::new(
+                ExpressionType::Abstraction(argument.clone(), scalars(e, &scales).into()),
+                expression.span,
+            )
+        }
+        ExpressionType::Application(f, a) => match &f.expression {
+            ExpressionType::Variable(v) if scales.contains(v.name) => {
+                let to = match Kind::simplify(v.kind.clone()).as_ref() {
+                    Kind::Abstraction(_, to) => to.clone(),
+                    _ => unreachable!("applied variables are given abstraction kinds"),
+                };
+
+                let scalar = Kind::Observable(Kind::Decimal.into()).into();
+                let variable = Variable::new(v.name, Kind::Abstraction(scalar, to).into(), v.span);
+                let argument = Observable::Fixed(scalars(a, scales).into());
+
+                Expression::new(
+                    ExpressionType::Application(
+                        Expression::new(variable.into(), f.span).into(),
+                        Expression::new(argument.into(), a.span).into(),
+                    ),
+                    expression.span,
+                )
+            }
+            _ => expression.map(|e| scalars(e, scales)),
+        },
+        _ => expression.map(|e| scalars(e, scales)),
+    }
+}
+
+// Resolves the kinds of the operands and result of additions, subtractions and multiplications
+// within expressions, once as much is known of them as possible, defaulting to all being of the
+// same kind. Contracts combined by `and`, `or`, `cond` and `then`, or their aliases, pay in the
+// unit of the contracts they combine, or in the mixed unit `*` if those pay in different units. The
+// kinds of multiplications, divisions and scalars, whose code depends on whether they are of words
+// or decimals, are then taken to be words unless known otherwise. Contracts scaled by amounts must
+// pay in the currencies of those amounts.
+fn resolve<'a>(expressions: &[&Expression<'a>], combinators: &HashSet<&'a str>) -> Result<'a, ()> {
+    type Combined<'a, 'b> = (&'b Expression<'a>, &'b Expression<'a>, &'b Expression<'a>);
+
+    struct Found<'a, 'b> {
+        arithmetic: Vec<&'b Expression<'a>>,
+        numbers: Vec<&'b Expression<'a>>,
+        scaled: Vec<(&'b Expression<'a>, &'b Expression<'a>)>,
+        combined: Vec<Combined<'a, 'b>>,
+    }
+
+    fn find<'a, 'b>(
+        expression: &'b Expression<'a>,
+        combinators: &HashSet<&'a str>,
+        found: &mut Found<'a, 'b>,
+    ) {
+        match &expression.expression {
+            // Only applications of `scale` are given scalars.
+            ExpressionType::Application(f, a) => {
+                if let ExpressionType::Observable(Observable::Fixed(e)) = &a.expression {
+                    found.scaled.push((f, e))
+                }
+
+                // A combinator is fully applied once it results in a contract, the last two of its
+                // arguments being the contracts it combines.
+                if let ExpressionType::Application(head, left) = &f.expression {
+                    let mut head = head;
+                    while let ExpressionType::Application(g, _) = &head.expression {
+                        head = g;
+                    }
+
+                    let contract =
+                        matches!(Kind::simplify(expression.kind()).as_ref(), Kind::State(_));
+
+                    match &head.expression {
+                        ExpressionType::Variable(v) if contract && combinators.contains(v.name) => {
+                            found.combined.push((expression, left, a))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ExpressionType::Abstraction(argument, e) if combinators.contains(argument.name) => {
+                let mut combinators = combinators.clone();
+                combinators.remove(argument.name);
+
+                return find(e, &combinators, found);
+            }
+            ExpressionType::Class(Class::Numerable(Numerable::Add(_, _, _)))
+            | ExpressionType::Class(Class::Numerable(Numerable::Subtract(_, _, _))) => {
+                found.arithmetic.push(expression)
+            }
+            ExpressionType::Class(Class::Numerable(Numerable::Multiply(_, _, _))) => {
+                found.arithmetic.push(expression);
+                found.numbers.push(expression)
+            }
+            ExpressionType::Class(Class::Numerable(Numerable::Divide(_, _)))
+            | ExpressionType::Observable(Observable::Fixed(_)) => found.numbers.push(expression),
+            _ => {}
+        }
+
+        for child in expression.children() {
+            find(child, combinators, found);
+        }
+    }
+
+    // The unit a contract pays in.
+    fn unit(kind: Rc<Kind>) -> Rc<Kind> {
+        match Kind::simplify(kind).as_ref() {
+            Kind::State(unit) => Kind::simplify(unit.clone()),
+            _ => unreachable!("combinators combine contracts"),
+        }
+    }
+
+    fn default<'a>(kind: Rc<Kind>) -> result::Result<(), Error<'a>> {
+        let kind = Kind::simplify(kind);
+
+        match kind.as_ref() {
+            Kind::Observable(k) => default(k.clone()),
+            Kind::Unresolved(_) => kind.unify(Kind::Word.into()),
+            _ => Ok(()),
+        }
+    }
+
+    let mut found = Found {
+        arithmetic: Vec::new(),
+        numbers: Vec::new(),
+        scaled: Vec::new(),
+        combined: Vec::new(),
+    };
+
+    for expression in expressions {
+        find(expression, combinators, &mut found);
+    }
+
+    let Found {
+        arithmetic: mut pending,
+        numbers,
+        scaled,
+        mut combined,
+    } = found;
+
+    // Scalars are observable, although the kind of what is observed may not yet be known.
+    for expression in &numbers {
+        if let ExpressionType::Observable(Observable::Fixed(e)) = &expression.expression {
+            let observable: Rc<Kind> = Kind::Observable(Kind::default().into()).into();
+            observable.unify(e.kind()).map_err(|error| {
+                Err::Failure(Error::from_sprint_error(
+                    error.sprint_error.unwrap(),
+                    e.span,
+                ))
+            })?;
+        }
+    }
+
+    while !pending.is_empty() {
+        let mut unresolved = Vec::new();
+
+        for expression in &pending {
+            if let ExpressionType::Class(class) = &expression.expression {
+                if !arithmetic(class, false).map_err(|error| at(error, expression.span.unwrap()))? {
+                    unresolved.push(*expression);
+                }
+            }
+        }
+
+        // Once nothing more can be resolved, the first of the rest is given a default.
+        if unresolved.len() == pending.len() {
+            if let ExpressionType::Class(class) = &unresolved[0].expression {
+                arithmetic(class, true).map_err(|error| at(error, unresolved[0].span.unwrap()))?;
+            }
+
+            unresolved.remove(0);
+        }
+
+        pending = unresolved;
+    }
+
+    // Combined contracts whose units are not yet known are left as they are, so that they pay in
+    // whichever unit they are used at.
+    loop {
+        let mut unresolved = Vec::new();
+
+        for (result, left, right) in &combined {
+            let (l, r) = (unit(left.kind()), unit(right.kind()));
+            let paid = match (l.as_ref(), r.as_ref()) {
+                _ if Rc::ptr_eq(&l, &r) => l.clone(),
+                (Kind::Unit(this), Kind::Unit(other)) if this == other => l.clone(),
+                (Kind::Unit(_), Kind::Unit(_)) => Kind::Unit("*".to_string()).into(),
+                _ => {
+                    unresolved.push((*result, *left, *right));
+                    continue;
+                }
+            };
+
+            Rc::new(Kind::State(paid))
+                .unify(result.kind())
+                .map_err(|error| {
+                    Err::Failure(Error::from_sprint_error(
+                        error.sprint_error.unwrap(),
+                        result.span,
+                    ))
+                })?;
+        }
+
+        if unresolved.len() == combined.len() {
+            break;
+        }
+
+        combined = unresolved;
+    }
+
+    // Contracts can only be scaled by amounts of the currency they pay in.
+    for (scale, scalar) in scaled {
+        let amount = match Kind::simplify(scalar.kind()).as_ref() {
+            Kind::Observable(k) => Kind::simplify(k.clone()),
+            _ => unreachable!("scalars are observable"),
+        };
+
+        if let Kind::Amount(_) = amount.as_ref() {
+            let unit = Rc::new(Kind::default());
+            let contract =
+                Kind::Abstraction(Kind::State(unit.clone()).into(), Kind::default().into());
+
+            Rc::new(Kind::Abstraction(Kind::default().into(), contract.into()))
+                .unify(scale.kind())
+                .and_then(|_| Rc::new(Kind::Amount(unit)).unify(amount))
+                .map_err(|error| {
+                    Err::Failure(Error::from_sprint_error(
+                        error.sprint_error.unwrap(),
+                        scalar.span,
+                    ))
+                })?;
+        }
+    }
+
+    for expression in numbers {
+        let kind = match &expression.expression {
+            ExpressionType::Class(class) => class.operand_kind(),
+            _ => expression.children()[0].kind(),
+        };
+
+        default(kind).map_err(Err::Error)?;
+    }
+
+    Ok(())
+}
+
+// Unifies the kinds of an addition or subtraction, which can be of words or durations, or of a
+// date and a duration resulting in a date, while subtracting a date from another results in a
+// duration. Multiplying an amount by an exchange rate results in an amount of the currency it is
+// converted to. Returns whether enough was known of the kinds to tell which, unless given a
+// default of all being of the same kind.
+fn arithmetic<'a>(class: &Class<'a>, default: bool) -> result::Result<bool, Error<'a>> {
+    enum Arithmetic {
+        Numeric,
+        Offset,
+        Difference,
+        Exchange(Rc<Kind>, Rc<Kind>),
+    }
+
+    let (left, right, result, operator) = match class {
+        Class::Numerable(Numerable::Add(l, r, k)) => (l.kind(), r.kind(), k.clone(), '+'),
+        Class::Numerable(Numerable::Subtract(l, r, k)) => (l.kind(), r.kind(), k.clone(), '-'),
+        Class::Numerable(Numerable::Multiply(l, r, k)) => (l.kind(), r.kind(), k.clone(), '*'),
+        _ => return Ok(true),
+    };
+
+    let mut kinds = [left, right, result];
+
+    // Observables are unwrapped together, as either all or none of the kinds are observable.
+    let observable =
+        |kind: &Rc<Kind>| matches!(Kind::simplify(kind.clone()).as_ref(), Kind::Observable(_));
+
+    if kinds.iter().any(observable) {
+        for kind in kinds.iter_mut() {
+            let inner = Rc::new(Kind::default());
+            kind.clone().unify(Kind::Observable(inner.clone()).into())?;
+            *kind = inner;
+        }
+    }
+
+    let [left, right, result] = kinds;
+    let (l, r, k) = (
+        Kind::simplify(left.clone()),
+        Kind::simplify(right.clone()),
+        Kind::simplify(result.clone()),
+    );
+
+    let date = |kind: &Rc<Kind>| matches!(kind.as_ref(), Kind::Date);
+    let duration = |kind: &Rc<Kind>| matches!(kind.as_ref(), Kind::Duration);
+    let unknown = |kind: &Rc<Kind>| matches!(kind.as_ref(), Kind::Unresolved(_));
+    let amount = |kind: &Rc<Kind>| matches!(kind.as_ref(), Kind::Amount(_));
+    let rate = |kind: &Rc<Kind>| match kind.as_ref() {
+        Kind::Rate(from, to) => Some(Arithmetic::Exchange(from.clone(), to.clone())),
+        _ => None,
+    };
+
+    let arithmetic = if operator == '*' {
+        match rate(&l).or_else(|| rate(&r)) {
+            Some(exchange) => Some(exchange),
+            None if (amount(&l) || unknown(&l)) && (amount(&r) || unknown(&r)) => None,
+            None => Some(Arithmetic::Numeric),
+        }
+    } else if operator == '+' {
+        if date(&l) || date(&r) || date(&k) {
+            Some(Arithmetic::Offset)
+        } else if unknown(&l) && unknown(&k) && (duration(&r) || unknown(&r)) {
+            None
+        } else {
+            Some(Arithmetic::Numeric)
+        }
+    } else if date(&k) || (date(&l) && !unknown(&r) && !date(&r)) {
+        Some(Arithmetic::Offset)
+    } else if date(&r) || (date(&l) && !unknown(&k)) {
+        Some(Arithmetic::Difference)
+    } else if date(&l) {
+        Some(Arithmetic::Difference).filter(|_| default)
+    } else if unknown(&l) && (duration(&r) || unknown(&r)) && (duration(&k) || unknown(&k)) {
+        None
+    } else {
+        Some(Arithmetic::Numeric)
+    };
+
+    let (left_kind, right_kind, result_kind) = match arithmetic {
+        None if !default => return Ok(false),
+        None | Some(Arithmetic::Numeric) => (l.clone(), l.clone(), l),
+        Some(Arithmetic::Offset) => (Kind::Date.into(), Kind::Duration.into(), Kind::Date.into()),
+        Some(Arithmetic::Difference) => {
+            (Kind::Date.into(), Kind::Date.into(), Kind::Duration.into())
+        }
+        Some(Arithmetic::Exchange(from, to)) => {
+            let (amount, result) = (Kind::Amount(from).into(), Kind::Amount(to).into());
+
+            match rate(&l) {
+                Some(_) => (l, amount, result),
+                None => (amount, r, result),
+            }
+        }
+    };
+
+    left_kind.unify(left)?;
+    right_kind.unify(right)?;
+    result_kind.unify(result)?;
+
+    Ok(true)
+}
+
+// Checks that operators are only applied to kinds with an instance of their class. This can only
+// be done once all definitions have been unified, as the kinds of operands are not known before.
+fn instances<'a>(expression: &Expression<'a>) -> Result<'a, ()> {
+    match &expression.expression {
+        ExpressionType::Abstraction(_, e) => instances(e),
+        ExpressionType::Application(f, a) => {
+            instances(f)?;
+            instances(a)
+        }
+        ExpressionType::Class(class) => {
+            instance(class, &class.operand_kind(), expression.span)?;
+            class.operands().into_iter().try_for_each(instances)
+        }
+        ExpressionType::Observable(observable) => {
+            // Contracts can only be scaled by words, decimals and amounts.
+            if let Observable::Fixed(e) = observable {
+                let kind = match Kind::simplify(e.kind()).as_ref() {
+                    Kind::Observable(k) => Kind::simplify(k.clone()),
+                    _ => unreachable!("scalars are observable"),
+                };
+
+                if !matches!(kind.as_ref(), Kind::Amount(_) | Kind::Decimal | Kind::Word) {
+                    return Err(Err::Error(Error::from_sprint_error(
+                        SprintError::MissingInstanceError("Num", kind.as_ref().clone()),
+                        e.span,
+                    )));
+                }
+            }
+
+            observable.operands().into_iter().try_for_each(instances)
+        }
+        ExpressionType::State(state) => state.transitions().iter().try_for_each(|transition| {
+            transition.conditions().iter().try_for_each(instances)?;
+
+            for effect in transition.effects() {
+                match effect {
+                    Effect::GiveTo(e)
+                    | Effect::Guard(e)
+                    | Effect::PayTo(e)
+                    | Effect::Scale(e)
+                    | Effect::Spawn(e)
+                    | Effect::Withdraw(e) => instances(e)?,
+                    Effect::Flip => {}
+                }
+            }
+
+            instances(transition.next())
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Checks that a class has an instance for the kind of its operands, where it was required to.
+fn instance<'a>(class: &Class<'a>, kind: &Rc<Kind>, span: Option<Span<'a>>) -> Result<'a, ()> {
+    let kind = Kind::simplify(kind.clone());
+
+    if !class.is_exchange() && !class.is_instance(&kind) {
+        let error = match class.name() {
+            Some(name) => SprintError::MissingInstanceError(name, kind.as_ref().clone()),
+            None => SprintError::MismatchedKinds(Kind::Boolean, kind.as_ref().clone()),
+        };
+
+        return Err(Err::Error(Error::from_sprint_error(error, span)));
+    }
+
+    Ok(())
+}
+
+// Checks that durations are only compared, added or subtracted where they are known to count
+// either only months or only seconds, as durations are words holding months above seconds, which
+// only compare as their lengths do if they count the same.
+fn durations<'a>(name: &'a str, expression: &Expression<'a>) -> Result<'a, ()> {
+    fn duration(kind: Rc<Kind>) -> bool {
+        match Kind::simplify(kind).as_ref() {
+            Kind::Observable(k) => duration(k.clone()),
+            Kind::Duration => true,
+            _ => false,
+        }
+    }
+
+    // Whether a duration may count months, and whether it may count seconds.
+    fn parts(expression: &Expression) -> (bool, bool) {
+        match &expression.expression {
+            ExpressionType::Duration(d) => (d.months > 0, d.seconds > 0),
+            ExpressionType::Observable(Observable::Konst(e)) => parts(e),
+            ExpressionType::Class(Class::Numerable(Numerable::Subtract(l, _, _)))
+                if !duration(l.kind()) =>
+            {
+                (false, true)
+            }
+            ExpressionType::Class(Class::Numerable(Numerable::Add(l, r, _)))
+            | ExpressionType::Class(Class::Numerable(Numerable::Subtract(l, r, _))) => {
+                let ((l_months, l_seconds), (r_months, r_seconds)) = (parts(l), parts(r));
+                (l_months || r_months, l_seconds || r_seconds)
+            }
+            _ => (true, true),
+        }
+    }
+
+    if let ExpressionType::Class(class) = &expression.expression {
+        let operands = match class {
+            Class::Numerable(Numerable::Add(l, r, _))
+            | Class::Numerable(Numerable::Subtract(l, r, _))
+                if duration(l.kind()) =>
+            {
+                Some((l, r))
+            }
+            Class::Comparable(Comparable::Greater(l, r))
+            | Class::Comparable(Comparable::Less(l, r))
+            | Class::Comparable(Comparable::GreaterEqual(l, r))
+            | Class::Comparable(Comparable::LessEqual(l, r))
+            | Class::Equatable(Equatable::Equal(l, r))
+            | Class::Equatable(Equatable::NotEqual(l, r))
+                if duration(l.kind()) =>
+            {
+                Some((l, r))
+            }
+            _ => None,
+        };
+
+        if let Some((left, right)) = operands {
+            let ((l_months, l_seconds), (r_months, r_seconds)) = (parts(left), parts(right));
+
+            if (l_months || r_months) && (l_seconds || r_seconds) {
+                return Err(Err::Failure(Error::from_sprint_error(
+                    SprintError::TypeError(name, SprintError::MixedDurationsError.into()),
+                    expression.span,
+                )));
+            }
+        }
+    }
+
+    expression
+        .children()
+        .into_iter()
+        .try_for_each(|child| durations(name, child))
+}
+
+// Attaches the span of the expression being built to a unification error. This is a failure
+// rather than an error, as backtracking would only hide the mismatch behind a less useful error.
+fn type_error<'a>(name: &'a str, error: Error<'a>, span: Option<Span<'a>>) -> Err<Error<'a>> {
+    Err::Failure(Error::from_sprint_error(
+        SprintError::TypeError(name, error.sprint_error.unwrap().into()),
+        span,
+    ))
+}
+
+fn at<'a>(error: Error<'a>, span: Span<'a>) -> Err<Error<'a>> {
+    Err::Failure(Error::from_sprint_error(
+        error.sprint_error.unwrap(),
+        Some(span),
+    ))
+}
+
 fn unify_context<'a>(
     context: Result<'a, Context<'a, ()>>,
     definition: Context<'a, Expression<'a>>,
@@ -1,14 +1,17 @@
 use super::IResult;
 use nom::{
-    character::complete::char,
-    character::complete::multispace0,
+    branch::alt,
+    bytes::complete::{tag, take_till},
+    character::complete::{anychar, char, multispace1},
+    combinator::{not, recognize},
     error::ParseError,
-    multi::{count, many1_count},
-    sequence::{delimited, terminated},
-    AsBytes, AsChar, InputIter, InputTakeAtPosition, Slice,
+    multi::{many0, many1},
+    sequence::{delimited, preceded},
+    AsBytes, AsChar, Compare, InputIter, InputLength, InputTake, InputTakeAtPosition, Offset,
+    Slice,
 };
 use nom_locate::LocatedSpan;
-use std::ops::RangeFrom;
+use std::ops::{RangeFrom, RangeTo};
 
 pub fn span<'a, I, O, F>(f: F) -> impl Fn(I) -> IResult<'a, I, O>
 where
@@ -23,12 +26,74 @@ where
 
 pub fn padding0<I, O, E, F>(f: F) -> impl Fn(I) -> nom::IResult<I, O, E>
 where
-    I: InputTakeAtPosition,
+    I: Clone + PartialEq + Compare<&'static str> + InputTake + InputIter + InputLength,
+    I: InputTakeAtPosition + Offset + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputIter>::Item: AsChar,
     <I as InputTakeAtPosition>::Item: AsChar + Clone,
     E: ParseError<I>,
     F: Fn(I) -> nom::IResult<I, O, E>,
 {
-    delimited(multispace0, f, multispace0)
+    delimited(whitespace0, f, whitespace0)
+}
+
+/// Whitespace, including new lines and comments.
+pub fn whitespace0<I, E>(input: I) -> nom::IResult<I, I, E>
+where
+    I: Clone + PartialEq + Compare<&'static str> + InputTake + InputIter + InputLength,
+    I: InputTakeAtPosition + Offset + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputIter>::Item: AsChar,
+    <I as InputTakeAtPosition>::Item: AsChar + Clone,
+    E: ParseError<I>,
+{
+    recognize(many0(alt((multispace1, comment))))(input)
+}
+
+pub fn whitespace1<I, E>(input: I) -> nom::IResult<I, I, E>
+where
+    I: Clone + PartialEq + Compare<&'static str> + InputTake + InputIter + InputLength,
+    I: InputTakeAtPosition + Offset + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputIter>::Item: AsChar,
+    <I as InputTakeAtPosition>::Item: AsChar + Clone,
+    E: ParseError<I>,
+{
+    recognize(many1(alt((multispace1, comment))))(input)
+}
+
+/// A `--` comment until the end of the line, or a `{- -}` comment which can be nested.
+pub fn comment<I, E>(input: I) -> nom::IResult<I, I, E>
+where
+    I: Clone + PartialEq + Compare<&'static str> + InputTake + InputIter + InputLength,
+    I: InputTakeAtPosition + Offset + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputIter>::Item: AsChar,
+    <I as InputTakeAtPosition>::Item: AsChar,
+    E: ParseError<I>,
+{
+    let line = preceded(
+        tag("--"),
+        take_till(|c: <I as InputTakeAtPosition>::Item| {
+            let c = c.as_char();
+            c == '\n' || c == '\r'
+        }),
+    );
+
+    alt((recognize(line), block_comment))(input)
+}
+
+fn block_comment<I, E>(input: I) -> nom::IResult<I, I, E>
+where
+    I: Clone + PartialEq + Compare<&'static str> + InputTake + InputIter + InputLength,
+    I: Offset + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    let text = preceded(not(alt((tag("{-"), tag("-}")))), anychar);
+    let block = delimited(
+        tag("{-"),
+        many0(alt((block_comment, recognize(text)))),
+        tag("-}"),
+    );
+
+    recognize(block)(input)
 }
 
 pub fn brackets1<I, O, E, F>(f: F) -> impl Fn(I) -> nom::IResult<I, O, E>
@@ -39,8 +104,9 @@ where
     F: Fn(I) -> nom::IResult<I, O, E> + Copy,
 {
     move |input: I| {
-        let (input, brackets) = many1_count(char('('))(input)?;
-        terminated(f, count(char(')'), brackets))(input)
+        // Try the inner parser before another level of brackets, so that `((a + b) * c)` is not
+        // mistaken for a doubly-bracketed expression.
+        delimited(char('('), alt((f, brackets1(f))), char(')'))(input)
     }
 }
 
@@ -99,9 +165,23 @@ mod tests {
         assert_eq!(padding0(parser)("  abc  "), Ok(("", "abc")));
     }
 
+    #[test]
+    fn parse_comment() {
+        assert_eq!(comment::<_, ()>("-- abc\nd"), Ok(("\nd", "-- abc")));
+        assert_eq!(
+            comment::<_, ()>("{- a {- b -} -}c"),
+            Ok(("c", "{- a {- b -} -}"))
+        );
+        assert_eq!(comment::<_, ()>("{- -- -}c"), Ok(("c", "{- -- -}")));
+        comment::<_, ()>("{- {- -}").unwrap_err();
+        comment::<_, ()>("- a").unwrap_err();
+        assert_eq!(padding0(parser)(" -- a\n {- b -}abc -- c"), Ok(("", "abc")));
+    }
+
     #[test]
     fn parse_brackets1() {
         assert_eq!(brackets1(parser)("(abc)"), Ok(("", "abc")));
+        assert_eq!(brackets1(parser)("((abc))"), Ok(("", "abc")));
         brackets1(parser)("(abc").unwrap_err();
         brackets1(parser)("((abc").unwrap_err();
         brackets1(parser)("((abc)").unwrap_err();
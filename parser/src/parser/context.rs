@@ -1,21 +1,41 @@
 use crate::{
-    ast::{Definition, Variable},
+    ast::{Definition, Kind, Variable},
     hash_count::HashCount,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 #[derive(Clone, Debug)]
 pub struct Context<'a, T> {
     inner: T,
     pub definitions: HashMap<&'a str, Definition<'a>>,
+    pub signatures: HashMap<&'a str, Signature<'a>>,
     pub variables: HashCount<Variable<'a>>,
 }
 
+#[derive(Clone, Debug)]
+pub struct Signature<'a> {
+    pub variable: Variable<'a>,
+    /// The kind variables of the signature, which must remain distinct and unresolved.
+    pub parameters: Vec<Rc<Kind>>,
+}
+
+impl<'a> Signature<'a> {
+    pub fn new(variable: Variable<'a>) -> Self {
+        let parameters = Kind::unresolved(variable.kind.clone());
+
+        Self {
+            variable,
+            parameters,
+        }
+    }
+}
+
 impl<T> From<T> for Context<'_, T> {
     fn from(inner: T) -> Self {
         Self {
             inner,
             definitions: Default::default(),
+            signatures: Default::default(),
             variables: Default::default(),
         }
     }
@@ -26,6 +46,7 @@ impl<'a, T> Context<'a, T> {
         Context {
             inner: f(self.inner),
             definitions: self.definitions,
+            signatures: self.signatures,
             variables: self.variables,
         }
     }
@@ -35,6 +56,7 @@ impl<'a, T> Context<'a, T> {
             Context {
                 inner: (),
                 definitions: self.definitions,
+                signatures: self.signatures,
                 variables: self.variables,
             },
             self.inner,
@@ -1,6 +1,20 @@
-use super::{IResult, Span};
-use crate::ast::{Date, Expression, ExpressionType};
-use nom::{bytes::complete::tag, character::complete::digit1, combinator::opt};
+use super::{
+    error::{Error, SprintError},
+    IResult, Span,
+};
+use crate::ast::{Date, Duration, Expression, ExpressionType, Kind};
+use chrono::NaiveDate;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, digit1, one_of},
+    combinator::{map, map_res, not, opt},
+    sequence::{pair, preceded, separated_pair, terminated},
+    Err,
+};
+use std::convert::TryFrom;
+
+const DAY: u64 = 24 * 60 * 60;
 
 pub fn date(input: Span) -> IResult<Span, Expression> {
     let span = input;
@@ -41,21 +55,116 @@ pub fn date(input: Span) -> IResult<Span, Expression> {
         (input, Some(second)) => (input, second),
         (input, None) => (input, 0),
     };
-    let (input, _) = tag("Z")(input)?;
 
-    // Year is represented as an i32 (compared to u32).
+    // Timestamps are whole seconds, so any fraction of a second is dropped.
+    let (input, _) = opt(preceded(tag("."), digit1))(input)?;
+    let (input, offset) = offset(input)?;
+
+    let timestamp = offset
+        .and_then(|offset| timestamp(year, month, day, hour, minute, second, offset))
+        .ok_or_else(|| {
+            let literal = &span.fragment[..span.fragment.len() - input.fragment.len()];
+            Err::Failure(Error::from_sprint_error(
+                SprintError::InvalidDateError(literal),
+                Some(span),
+            ))
+        })?;
+
     Ok((
         input,
-        Expression::new(
-            ExpressionType::Date(Date::Date(year, month, day, hour, minute, second)),
-            Some(span),
+        Expression::new(ExpressionType::Date(Date::Date(timestamp)), Some(span)),
+    ))
+}
+
+/// Parses the offset of a date from UTC, either `Z` or of the form `+hh:mm` or `-hh:mm`, as a
+/// signed number of minutes, or `None` if it is out of range.
+fn offset(input: Span) -> IResult<Span, Option<i64>> {
+    alt((
+        map(tag("Z"), |_| Some(0)),
+        map(
+            pair(one_of("+-"), separated_pair(digits, tag(":"), digits)),
+            |(sign, (hours, minutes))| {
+                if hours >= 24 || minutes >= 60 {
+                    return None;
+                }
+
+                let minutes = (hours * 60 + minutes) as i64;
+                Some(if sign == '-' { -minutes } else { minutes })
+            },
         ),
+    ))(input)
+}
+
+/// Converts a date with an offset from UTC to a Unix timestamp, or `None` if it does not exist or
+/// is before 1970.
+fn timestamp(
+    year: u64,
+    month: u64,
+    day: u64,
+    hour: u64,
+    minute: u64,
+    second: u64,
+    offset: i64,
+) -> Option<u64> {
+    let date = NaiveDate::from_ymd_opt(
+        i32::try_from(year).ok()?,
+        u32::try_from(month).ok()?,
+        u32::try_from(day).ok()?,
+    )?
+    .and_hms_opt(
+        u32::try_from(hour).ok()?,
+        u32::try_from(minute).ok()?,
+        u32::try_from(second).ok()?,
+    )?;
+
+    u64::try_from(
+        date.checked_sub_signed(chrono::Duration::minutes(offset))?
+            .and_utc()
+            .timestamp(),
+    )
+    .ok()
+}
+
+/// Parses a duration of a number of days, months or years, e.g. `30d`, `6m` or `1y`, where days
+/// are a number of seconds and years are 12 months.
+pub fn duration(input: Span) -> IResult<Span, Expression> {
+    let span = input;
+    let unit = terminated(one_of("dmy"), not(alphanumeric1));
+
+    let (input, (n, unit)) = pair(digit1, unit)(input)?;
+    let duration = n
+        .fragment
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| match unit {
+            'd' => Some(Duration {
+                months: 0,
+                seconds: n.checked_mul(DAY)?,
+            }),
+            'm' => Some(Duration {
+                months: n,
+                seconds: 0,
+            }),
+            _ => Some(Duration {
+                months: n.checked_mul(12)?,
+                seconds: 0,
+            }),
+        })
+        .filter(|duration| duration.word().is_some())
+        .ok_or_else(|| {
+            let literal = &span.fragment[..span.fragment.len() - input.fragment.len()];
+            Err::Failure(Error::from_sprint_error(
+                SprintError::OutOfRangeError(literal, Kind::Duration),
+                Some(span),
+            ))
+        })?;
+
+    Ok((
+        input,
+        Expression::new(ExpressionType::Duration(duration), Some(span)),
     ))
 }
 
 fn digits(input: Span) -> IResult<Span, u64> {
-    let (input, number) = digit1(input)?;
-    let number = number.fragment.parse::<u64>().unwrap();
-
-    Ok((input, number))
+    map_res(digit1, |number: Span| number.fragment.parse::<u64>())(input)
 }
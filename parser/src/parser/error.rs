@@ -21,7 +21,18 @@ pub enum SprintError<'a> {
     TypeError(&'a str, Box<SprintError<'a>>),
     MismatchedKinds(Kind, Kind),
     UnknownIdentifierError(&'a str, Kind),
+    MissingInstanceError(&'static str, Kind),
     DuplicateDefinitionError(&'a str),
+    GeneralSignatureError(&'a str, Kind),
+    UnknownModuleError(&'a str),
+    UnknownExportError(&'a str, &'a str),
+    CyclicImportError(&'a str),
+    InvalidDateError(&'a str),
+    InvalidScheduleError(&'static str),
+    OutOfRangeError(&'a str, Kind),
+    InvalidRolesError,
+    InvalidArgumentError(&'a str, Kind),
+    MixedDurationsError,
     InvalidNumberArgsError,
     UndefinedMainError,
 }
@@ -90,12 +101,50 @@ impl<'a> SprintError<'a> {
             Self::UnknownIdentifierError(id, kind) => {
                 format!("Unknown identifier: {} :: {}", id, kind)
             }
+            Self::MissingInstanceError(class, kind) => {
+                format!("No instance for ({} {})", class, kind)
+            }
             Self::InvalidNumberArgsError => {
                 String::from("Invalid number of arguments in primitive application")
             }
             Self::DuplicateDefinitionError(name) => {
                 format!("Duplicate definition for \"{}\"", name)
             }
+            Self::GeneralSignatureError(name, kind) => format!(
+                "The signature of \"{}\" is more general than its definition, of kind {}",
+                name, kind
+            ),
+            Self::UnknownModuleError(module) => format!("Unknown module \"{}\"", module),
+            Self::UnknownExportError(module, name) => {
+                format!("Module \"{}\" does not export \"{}\"", module, name)
+            }
+            Self::CyclicImportError(module) => {
+                format!("Module \"{}\" is imported by a module it imports", module)
+            }
+            Self::InvalidDateError(date) => format!(
+                "Invalid date \"{}\", which does not exist or is before 1970",
+                date
+            ),
+            Self::InvalidScheduleError(name) => format!(
+                "The schedule of \"{}\" must be given by a date, a duration and a number of \
+                 dates written literally, where neither of the last two is zero",
+                name
+            ),
+            Self::OutOfRangeError(literal, kind) => {
+                format!("The literal \"{}\" is out of range for {}", literal, kind)
+            }
+            Self::InvalidRolesError => String::from(
+                "Roles can only be declared once in a program, where there must be at least two",
+            ),
+            Self::InvalidArgumentError(name, kind) => format!(
+                "The argument \"{}\" of \"main\" is of kind {}, but only words, decimals, dates, \
+                 durations, booleans and amounts can be given when a contract is initialized",
+                name, kind
+            ),
+            Self::MixedDurationsError => String::from(
+                "durations can only be compared, added or subtracted if both count months or \
+                 both count days, as the length of a month depends on the date it is counted from",
+            ),
             Self::UndefinedMainError => {
                 String::from("No valid definition of the \"main\" contract was found")
             }
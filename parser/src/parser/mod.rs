@@ -3,12 +3,15 @@ mod combinator;
 mod context;
 mod date;
 mod error;
+mod module;
 mod primitive;
 mod program;
 mod unify;
 
-use self::{combinator::span, error::Error, program::program};
-use crate::ast::Definitions;
+pub use self::module::{Import, Module};
+
+use self::{combinator::span, error::Error};
+use crate::ast::{Definitions, Expression, ExpressionType};
 use nom::{
     combinator::{all_consuming, complete},
     Err,
@@ -18,21 +21,85 @@ use std::result;
 
 pub type Span<'a> = LocatedSpan<&'a str>;
 
+/// Owns the names made while checking a program, such as those qualified by their module, which
+/// live as long as its sources.
+pub type Arena = typed_arena::Arena<u8>;
+
 type Result<'a, T> = result::Result<T, Err<Error<'a>>>;
 
 type IResult<'a, I, O> = nom::IResult<I, O, Error<'a>>;
 
-pub fn contract<'a>(input: &'a str) -> result::Result<Definitions<'a>, Error> {
-    match span(all_consuming(complete(program)))(input) {
-        Ok((_, context)) => {
-            let variables = &context.variables;
-            Ok(context
-                .definitions
-                .into_iter()
-                .map(|(_, d)| d)
-                // TODO: giving "main" an initial count of 1 would be nicer.
-                .filter(|d| d.variable.name == "main" || variables.count(&d.variable) > 1)
-                .collect())
+/// Definitions written in Sprint which are available to every program.
+const PRELUDE: &str = include_str!("prelude.sprint");
+
+pub fn contract<'a>(
+    input: &'a str,
+    arena: &'a Arena,
+) -> result::Result<Definitions<'a>, Error<'a>> {
+    program(vec![module(input)?], arena)
+}
+
+/// Parses the definitions of a module, without resolving its imports.
+pub fn module<'a>(input: &'a str) -> result::Result<Module<'a>, Error<'a>> {
+    match span(all_consuming(complete(program::module)))(input) {
+        Ok((_, module)) => Ok(module),
+        Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => Err(error),
+        _ => unreachable!(),
+    }
+}
+
+/// Parses a literal given as an argument of `main` when a contract is initialized, such as `1.5`
+/// or `2020-01-01T00:00:00Z`.
+pub fn literal<'a>(input: &'a str) -> result::Result<Expression<'a>, Error<'a>> {
+    match span(all_consuming(complete(program::literal)))(input) {
+        Ok((_, literal)) => Ok(literal),
+        Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => Err(error),
+        _ => unreachable!(),
+    }
+}
+
+/// Checks a program made up of modules, the first of which defines `main`, along with every
+/// module it imports, directly or otherwise.
+pub fn program<'a>(
+    modules: Vec<Module<'a>>,
+    arena: &'a Arena,
+) -> result::Result<Definitions<'a>, Error<'a>> {
+    let prelude = module(PRELUDE).expect("the prelude is valid");
+
+    match builder::program(prelude, modules, arena) {
+        Ok(mut context) => {
+            // Only the definitions used by "main" are kept, leaving out most of the prelude.
+            let mut used = vec![context.definitions.remove("main").unwrap()];
+            let mut i = 0;
+
+            while i < used.len() {
+                let names: Vec<_> = used[i]
+                    .expression
+                    .free_variables()
+                    .into_iter()
+                    .map(|variable| variable.name)
+                    .collect();
+
+                used.extend(
+                    names
+                        .into_iter()
+                        .filter_map(|name| context.definitions.remove(name)),
+                );
+                i += 1;
+            }
+
+            // Roles are bound when a contract is initialized, so they are kept even if unused.
+            used.extend(
+                context
+                    .definitions
+                    .drain()
+                    .map(|(_, d)| d)
+                    .filter(|definition| {
+                        matches!(definition.expression.expression, ExpressionType::Role(_, _))
+                    }),
+            );
+
+            Ok(used)
         }
         Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => Err(error),
         _ => unreachable!(),
@@ -41,10 +108,1161 @@ pub fn contract<'a>(input: &'a str) -> result::Result<Definitions<'a>, Error> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{error::SprintError, *};
+    use crate::ast::{Date, Definition, Expression, ExpressionType, Kind};
 
     #[test]
     fn parse_contract() {
-        assert!(contract("main = zero").is_ok());
+        assert!(contract("main = zero", &Arena::new()).is_ok());
+    }
+
+    #[test]
+    fn parse_arithmetic() {
+        assert!(contract(
+            "main = scale (konst ((2 + 3) * 4 - 10 / 2)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst (- 0 * 3)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "f n = scale (konst (n - 2)) (one \"USD\")\nmain = f 3",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst 2 * konst 3) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst (2 + True)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("main = zero + (one \"USD\")", &Arena::new()).is_err());
+
+        // Arithmetic on word literals is evaluated, so that negative words are rejected.
+        for (source, literal) in &[
+            ("konst (-1)", "-1"),
+            ("konst (- 2 * 3)", "- 2 * 3"),
+            ("konst (1 - 2)", "1 - 2"),
+            ("konst (2 + 3 - 6)", "2 + 3 - 6"),
+            ("konst (4000000000 * 5000000000)", "4000000000 * 5000000000"),
+        ] {
+            assert_eq!(
+                contract(
+                    &format!("main = scale ({}) (one \"USD\")", source),
+                    &Arena::new()
+                )
+                .unwrap_err()
+                .sprint_error,
+                Some(SprintError::OutOfRangeError(literal, Kind::Word))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_comparison() {
+        assert!(contract(
+            "x = 1 + 2 < 4 && not (3 == 4) || 5 /= 6\nmain = zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "x = konst 1 >= konst 2 || konst True\nmain = zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("x = (1 <= 2) == True\nmain = zero", &Arena::new()).is_ok());
+        assert!(contract("x = 1 < 2 < 3\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x = 1 && True\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x = zero == one \"USD\"\nmain = zero", &Arena::new()).is_err());
+        assert_eq!(
+            contract("x = not 1\nmain = zero", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::MismatchedKinds(Kind::Boolean, Kind::Word))
+        );
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert!(contract(
+            "main = after (2020-12-25T00:00:00Z + 30d) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = before (2020-12-25T00:00:00Z - 6m - 1y) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "x = 2021-01-01T00:00:00Z - 2020-01-01T00:00:00Z > 365d\nmain = zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("x = 1y + 6m < 2y\nmain = zero", &Arena::new()).is_ok());
+        assert!(matches!(
+            contract(
+                "x t1 t2 = t2 - t1 > 1y\nmain = zero",
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::TypeError("x", e)) if *e == SprintError::MixedDurationsError
+        ));
+        assert!(contract("x = 1m > 40d\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x = 1m - 1d\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x d = d + 1d\nmain = zero", &Arena::new()).is_err());
+        assert!(contract(
+            "f :: Date -> Duration -> Date\nf t d = t + d\n\
+             g :: Date -> Date -> Bool\ng a b = a - b < 30d\n\
+             main = cond (konst (g (f 2020-12-25T00:00:00Z 1d) 2020-12-25T00:00:00Z)) (one \"USD\") zero", &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract(
+            "main = after (2020-12-25T00:00:00Z + 2020-12-25T00:00:00Z) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = after (1d + 2020-12-25T00:00:00Z) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("x = 1 + 30d\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x = 2 * 30d\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("x = 30dd\nmain = zero", &Arena::new()).is_err());
+        assert!(contract(
+            "f :: Date -> Date\nf t = t - 1y\nmain = zero",
+            &Arena::new()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn parse_dates() {
+        fn date<'a>(date: &str, arena: &'a Arena) -> result::Result<u64, Error<'a>> {
+            let source = arena.alloc_str(&format!("main = after t (one \"USD\")\nt = {}", date));
+            let definitions = contract(source, arena)?;
+            match definitions.iter().find(|d| d.variable.name == "t") {
+                Some(Definition {
+                    expression:
+                        Expression {
+                            expression: ExpressionType::Date(Date::Date(timestamp)),
+                            ..
+                        },
+                    ..
+                }) => Ok(*timestamp),
+                _ => unreachable!(),
+            }
+        }
+
+        let arena = Arena::new();
+
+        assert_eq!(date("2020-12-25T00:00:00Z", &arena), Ok(1_608_854_400));
+        assert_eq!(date("2020-12-25T01:30:00+01:30", &arena), Ok(1_608_854_400));
+        assert_eq!(date("2020-12-24T19:00:00-05:00", &arena), Ok(1_608_854_400));
+        assert_eq!(date("2020-12-25T00:00:00.999Z", &arena), Ok(1_608_854_400));
+        assert_eq!(date("2020-02-29T00:00:00Z", &arena), Ok(1_582_934_400));
+
+        let error = date("2020-02-30T00:00:00Z", &arena).unwrap_err();
+        assert_eq!(
+            error.sprint_error,
+            Some(SprintError::InvalidDateError("2020-02-30T00:00:00Z"))
+        );
+        let nom_error = error.nom_error.unwrap();
+        assert_eq!((nom_error.line, nom_error.column), (2, 5));
+
+        assert!(date("2021-02-29T00:00:00Z", &arena).is_err());
+        assert!(date("2020-13-01T00:00:00Z", &arena).is_err());
+        assert!(date("2020-12-25T24:00:00Z", &arena).is_err());
+        assert!(date("2020-12-25T00:00:00+24:00", &arena).is_err());
+        assert!(date("1969-12-31T23:59:59Z", &arena).is_err());
+    }
+
+    #[test]
+    fn parse_observables() {
+        let call = "call :: Observable Word -> Word -> Contract\n\
+                    call s k = scale (max s (konst k) - konst k) (one \"USD\")\n";
+        assert!(contract(&format!("{}main = call (konst 60) 50", call), &Arena::new()).is_ok());
+        assert!(contract(
+            &format!(
+                "{}main = call (lift (\\x -> x * 2) (min (konst 30) (konst 40))) 50",
+                call
+            ),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "spread :: Word -> Word -> Word\nspread a b = a - b\n\
+             main = scale (lift2 spread (konst 10) (konst 3)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (lift (\\x -> x > 2) (konst 3)) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract(
+            "main = scale (max (konst True) (konst 1)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("main = scale (max 1 2) (one \"USD\")", &Arena::new()).is_err());
+        assert!(contract(
+            "main = scale (lift (\\x -> x) 2) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = scale (lift (\\x -> x > 2) (konst 3)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract("main = scale (min (konst 1)) (one \"USD\")", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::InvalidNumberArgsError)
+        );
+    }
+
+    #[test]
+    fn parse_named_observables() {
+        assert!(contract("main = scale (obs \"LIBOR\") (one \"USD\")", &Arena::new()).is_ok());
+        assert!(contract(
+            "main = scale (max (obs \"LIBOR\") (konst 1) * konst 2) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (obs \"LIBOR\" > konst 5) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract("main = scale (obs LIBOR) (one \"USD\")", &Arena::new()).is_err());
+        assert!(contract("main = scale obs (one \"USD\")", &Arena::new()).is_err());
+        assert!(contract(
+            "main = cond (obs \"LIBOR\") (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = scale (konst (obs \"LIBOR\")) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("obs = one \"USD\"\nmain = obs", &Arena::new()).is_err());
+    }
+
+    #[test]
+    fn parse_observed() {
+        let start = "2020-01-01T00:00:00Z";
+        assert!(contract(
+            &format!(
+                "main = scale (average {} 1d 12 (obs \"USD/EUR\")) (one \"USD\")",
+                start
+            ),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            &format!(
+            "main = scale (maxSince {} 7d 4 (konst 1) - minSince {} 7d 4 (konst 1)) (one \"USD\")",
+            start, start
+        ),
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract(
+            &format!(
+                "main = scale (average {} 1d 12 (konst True)) (one \"USD\")",
+                start
+            ),
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract(
+                &format!(
+                    "main = scale (average {} 1d 0 (konst 1)) (one \"USD\")",
+                    start
+                ),
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::InvalidScheduleError("average"))
+        );
+        assert_eq!(
+            contract(
+                &format!(
+                    "main = scale (maxSince {} (1d - 1d) 3 (konst 1)) (one \"USD\")",
+                    start
+                ),
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::InvalidScheduleError("maxSince"))
+        );
+        assert_eq!(
+            contract(
+                "main = scale (minSince now 1d 3 (konst 1)) (one \"USD\")",
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::InvalidScheduleError("minSince"))
+        );
+    }
+
+    #[test]
+    fn parse_decimals() {
+        assert!(contract(
+            "main = scale (konst 0.025) (scale (konst 1000) (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst (2.5 * 1.5 / 0.5 - 0.25)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "interest :: Decimal -> Decimal -> Decimal\ninterest r x = r * x\n\
+             main = scale (konst (interest 0.05 100.0)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (konst (0.5 < 1.0)) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract(
+            "main = scale (konst (1.5 + 2)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = scale (konst 1.5 * konst 2) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("main = scale 0.5 (one \"USD\")", &Arena::new()).is_err());
+        assert!(contract(
+            "m a b = a * b\nmain = scale (konst (m 1.5 2.0)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract("main = scale (konst True) (one \"USD\")", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::MissingInstanceError("Num", Kind::Boolean))
+        );
+    }
+
+    #[test]
+    fn parse_scale_aliases() {
+        assert!(contract("f = scale\nmain = f (konst 2) (one \"USD\")", &Arena::new()).is_ok());
+        assert!(contract(
+            "f = scale\ng = f\nmain = g (konst 2.5) (g (konst 2) (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("f scale = scale\nmain = f (one \"USD\")", &Arena::new()).is_ok());
+        assert!(contract(
+            "f = scale\ng f = f\nmain = g (scale (konst 2) (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract(
+            "f = scale\nmain = f (konst True) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "f = scale\nmain = f (konst (amount \"EUR\" 2.0)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_literals() {
+        assert!(contract(
+            "main = scale (konst 18446744073709551615) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = truncate (2020-01-01T00:00:00Z + 12725829d + 16777215m) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+
+        let arena = Arena::new();
+        let error = |source| contract(source, &arena).unwrap_err().sprint_error;
+        assert_eq!(
+            error("main = scale (konst 18446744073709551616) (one \"USD\")"),
+            Some(SprintError::OutOfRangeError(
+                "18446744073709551616",
+                Kind::Word
+            ))
+        );
+        assert_eq!(
+            error("main = truncate (2020-01-01T00:00:00Z + 12725830d) (one \"USD\")"),
+            Some(SprintError::OutOfRangeError("12725830d", Kind::Duration))
+        );
+        assert_eq!(
+            error("main = truncate (2020-01-01T00:00:00Z + 1398102y) (one \"USD\")"),
+            Some(SprintError::OutOfRangeError("1398102y", Kind::Duration))
+        );
+        assert_eq!(
+            error("main = scale (konst 1844674407370955161.6) (one \"USD\")"),
+            Some(SprintError::OutOfRangeError(
+                "1844674407370955161.6",
+                Kind::Decimal
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_currencies() {
+        assert!(contract(
+            "main = and (one \"USD\") (give (scale (konst 2) (one \"EUR\")))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "f :: Currency -> Contract\nf k = give (one k)\nmain = f \"GBP\"",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (konst (\"USD\" == \"USD\")) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (konst (\"USD\" == \"EUR\")) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract("main = one", &Arena::new()).is_err());
+        assert!(contract("main = one \"US D\"", &Arena::new()).is_err());
+        assert!(contract(
+            "main = cond (konst (\"USD\" == 1)) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = scale (konst (\"USD\" + \"EUR\")) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract("main = one 1", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::TypeError(
+                "one",
+                SprintError::MismatchedKinds(Kind::Currency(Kind::default().into()), Kind::Word)
+                    .into()
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_units() {
+        assert!(contract(
+            "main = scale (konst (amount \"USD\" 2.0)) (give (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst (amount \"USD\" 2.0) * fx \"USD\" \"EUR\") (one \"EUR\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = let r = fx \"USD\" \"EUR\" in scale (konst (amount \"USD\" 2.0) * r) (one \"EUR\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "leg :: Currency u -> Amount u -> Contract u\nleg k x = scale (konst x) (one k)\n\
+             main = and (leg \"USD\" (amount \"USD\" 1.0)) (leg \"EUR\" (amount \"EUR\" 1.0))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "leg :: Contract USD\nleg = and (one \"USD\") (give (one \"USD\"))\n\
+             main = scale (konst (amount \"USD\" 2.0)) (or leg (then leg zero))",
+            &Arena::new()
+        )
+        .is_ok());
+
+        let mismatched = |source| {
+            contract(source, &Arena::new())
+                .unwrap_err()
+                .sprint_error
+                .unwrap()
+                .pretty()
+        };
+
+        assert_eq!(
+            mismatched("main = scale (konst (amount \"USD\" 2.0)) (one \"EUR\")"),
+            "expected Amount EUR, got Amount USD"
+        );
+        assert_eq!(
+            mismatched(
+                "main = scale (konst (amount \"EUR\" 2.0) * fx \"USD\" \"EUR\") (one \"EUR\")"
+            ),
+            "expected Amount USD, got Amount EUR"
+        );
+        assert_eq!(
+            mismatched("main = scale (konst (2.0 * 3.0) * fx \"USD\" \"EUR\") (one \"EUR\")"),
+            "expected Amount USD, got Decimal"
+        );
+        assert_eq!(
+            mismatched(
+                "main = let r = fx \"USD\" \"EUR\" in scale (r * konst (amount \"EUR\" 2.0)) (one \"EUR\")"
+            ),
+            "expected Amount USD, got Amount EUR"
+        );
+        assert_eq!(
+            mismatched("leg :: Contract EUR\nleg = one \"USD\"\nmain = leg"),
+            "Type Error: From definition of \"one\" expected Contract USD, got Contract EUR "
+        );
+        assert_eq!(
+            mismatched(
+                "leg :: Contract USD\nleg = and (one \"EUR\") (give (one \"EUR\"))\nmain = leg"
+            ),
+            "expected Contract EUR, got Contract USD"
+        );
+        assert_eq!(
+            mismatched(
+                "both = and\nmain = scale (konst (amount \"USD\" 1.0)) (both (one \"EUR\") (one \"EUR\"))"
+            ),
+            "expected Amount EUR, got Amount USD"
+        );
+        assert_eq!(
+            mismatched(
+                "pair c = cond (konst (1 == 1)) c (then c c)\n\
+                 main = scale (konst (amount \"USD\" 1.0)) (pair (one \"EUR\"))"
+            ),
+            "expected Amount EUR, got Amount USD"
+        );
+        assert_eq!(
+            mismatched(
+                "main = scale (konst (amount \"USD\" 1.0)) (and (one \"USD\") (one \"EUR\"))"
+            ),
+            "expected Amount *, got Amount USD"
+        );
+        assert!(contract(
+            "main = scale (konst (amount \"USD\" 1.0 * amount \"USD\" 1.0)) zero",
+            &Arena::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_addresses() {
+        assert!(contract("main = payTo 0x2a (give (one \"USD\"))", &Arena::new()).is_ok());
+        assert!(contract(
+            "escrow :: Address -> Contract -> Contract\nescrow a c = payTo a c\n\
+             main = and (escrow 0xA1 (one \"USD\")) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = cond (konst (0x2a == 0x2b)) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+
+        assert!(contract("main = payTo 42 (one \"USD\")", &Arena::new()).is_err());
+        assert!(contract("main = payTo 0x2a", &Arena::new()).is_err());
+        assert!(contract(
+            "main = scale (konst (0x2a + 0x2b)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+
+        let address = format!("0x{}", "f".repeat(65));
+        assert_eq!(
+            contract(
+                &format!("main = payTo {} (one \"USD\")", address),
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::OutOfRangeError(&address, Kind::Address))
+        );
+    }
+
+    #[test]
+    fn parse_roles() {
+        let roles = "roles lender borrower agent\n";
+        assert!(contract(
+            &format!(
+            "{}main = and (giveTo agent (one \"USD\")) (cond (isRole lender) (one \"USD\") zero)",
+            roles
+        ),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            &format!(
+                "{}fee :: Role -> Contract\nfee r = giveTo r (one \"USD\")\nmain = fee agent",
+                roles
+            ),
+            &Arena::new()
+        )
+        .is_ok());
+
+        // Roles are kept when unused, as a contract is initialized with an address for each.
+        let source = format!("{}main = zero", roles);
+        let arena = Arena::new();
+        let definitions = contract(&source, &arena).unwrap();
+        let names: Vec<_> = definitions
+            .iter()
+            .map(|definition| definition.variable.name)
+            .collect();
+        assert_eq!(names.len(), 5);
+        assert!(["lender", "borrower", "agent"]
+            .iter()
+            .all(|role| names.contains(role)));
+
+        assert!(contract(
+            &format!("{}main = giveTo 0x2a (one \"USD\")", roles),
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(&format!("{}main = giveTo lender", roles), &Arena::new()).is_err());
+        assert!(contract("main = giveTo agent (one \"USD\")", &Arena::new()).is_err());
+
+        let arena = Arena::new();
+        let error = |source| contract(source, &arena).unwrap_err().sprint_error;
+        assert_eq!(
+            error("roles lender lender\nmain = zero"),
+            Some(SprintError::DuplicateDefinitionError("lender"))
+        );
+        assert_eq!(
+            error("roles lender\nmain = zero"),
+            Some(SprintError::InvalidRolesError)
+        );
+        assert_eq!(
+            error(&format!("{}roles payer payee\nmain = zero", roles)),
+            Some(SprintError::InvalidRolesError)
+        );
+    }
+
+    #[test]
+    fn parse_arguments() {
+        assert!(contract("main k = scale (konst k) (one \"USD\")", &Arena::new()).is_ok());
+        assert!(contract(
+            "main :: Decimal -> Date -> Bool -> Contract\n\
+             main k d b = cond (konst b) (truncate d (scale (konst k) (one \"USD\"))) zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main :: Amount USD -> Contract\nmain n = scale (konst n) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "roles lender borrower\nmain :: Decimal -> Contract\n\
+             main lender = scale (konst lender) (giveTo borrower (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(matches!(
+            literal("2020-01-01T00:00:00Z").unwrap().expression,
+            ExpressionType::Date(Date::Date(1577836800))
+        ));
+        assert!(matches!(
+            literal("1.5").unwrap().expression,
+            ExpressionType::Decimal(_)
+        ));
+        assert!(matches!(
+            literal("1m").unwrap().expression,
+            ExpressionType::Duration(_)
+        ));
+        assert!(matches!(
+            literal("True").unwrap().expression,
+            ExpressionType::Boolean(true)
+        ));
+
+        assert!(literal("1 + 1").is_err());
+        assert!(literal("\"USD\"").is_err());
+        assert!(contract("main :: Word -> Contract\nmain k = k", &Arena::new()).is_err());
+        assert!(matches!(
+            contract("main c = give c", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::InvalidArgumentError("c", Kind::State(_)))
+        ));
+        assert_eq!(
+            contract("main k = k + 1", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::TypeError(
+                "main",
+                SprintError::MismatchedKinds(Kind::State(Kind::default().into()), Kind::Word)
+                    .into()
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_horizon() {
+        assert!(contract(
+            "main = then (truncate 2020-12-25T00:00:00Z (one \"USD\")) (truncate 2021-12-25T00:00:00Z zero)", &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = then (scale (konst 2) (truncate 2020-12-25T00:00:00Z (one \"USD\"))) (give zero)", &Arena::new()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn parse_polymorphism() {
+        let twice = "twice :: (a -> a) -> a -> a\ntwice f x = f (f x)\n";
+        assert!(contract(
+            &format!(
+                "{}double x = x * 2\nx = twice double 1\nmain = twice give (one \"USD\")",
+                twice
+            ),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            &format!(
+                "{}double x = x * 2\nmain = twice (scale (konst 2)) (twice double True)",
+                twice
+            ),
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "same x = x\nmain = scale (konst (same 2)) (same (one \"USD\"))",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "inc x = x + x\nmain = scale (konst (inc 2)) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert_eq!(
+            contract(
+                "inc x = x + x\nmain = cond (konst (inc True)) (one \"USD\") zero",
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::MissingInstanceError("Num", Kind::Boolean))
+        );
+        assert!(contract(
+            "inc x = x + x\ntwice x = inc (inc x)\nmain = cond (konst (twice True)) zero zero",
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract(
+                "n x = not x\nmain = scale (konst (n 2)) (one \"USD\")",
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::MismatchedKinds(Kind::Boolean, Kind::Word))
+        );
+        assert!(contract("f :: a -> a\nf x = x + 1\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("f :: a -> b -> a\nf x y = y\nmain = zero", &Arena::new()).is_err());
+        assert!(contract("main = scale (one \"USD\") (one \"USD\")", &Arena::new()).is_err());
+    }
+
+    #[test]
+    fn parse_local_bindings() {
+        assert!(contract(
+            "main = let k = 2; j = k + 1 in scale (konst j) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = let one = 2 in scale (konst one) zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = let x = 1; x = True in cond (konst x) (one \"USD\") zero",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "f x = scale (konst y) (one \"USD\") where y = x * 2\nmain = f 3",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst k) (one \"USD\")\n  where\n    j = 2\n    k = j + 1\n",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = let k = True in scale (konst k) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = let k = j; j = 2 in scale (konst k) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(
+            "main = scale (let k = 2 in konst k) (one \"USD\")\nx = k",
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract("in = 1\nmain = one \"USD\"", &Arena::new()).is_err());
+    }
+
+    #[test]
+    fn parse_comments() {
+        assert!(contract(
+            "-- header\nmain :: Contract -- entrypoint\nmain = one \"USD\" -- trailing",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "{- a {- nested -} block\n-}\nmain = scale {- c -} (konst 2) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst k) (one \"USD\")\n  where\n    -- factor\n    k = 2",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("main = one \"USD\"\n{- -- -}\n", &Arena::new()).is_ok());
+        assert!(contract("main = one \"USD\" {- {- -}", &Arena::new()).is_err());
+
+        let arena = Arena::new();
+        let error = contract(
+            "-- c\n{- d\n-}\nmain = zero\nx = {- e -} one \"USD\" + -- f\n  1",
+            &arena,
+        )
+        .unwrap_err();
+        let nom_error = error.sprint_error.and(error.nom_error).unwrap();
+        assert_eq!((nom_error.line, nom_error.column), (5, 13));
+    }
+
+    #[test]
+    fn parse_layout() {
+        assert!(contract(
+            "main =\n  scale\n    (konst 2)\n\n    (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale\n  -- factor\n  (konst 2) (one \"USD\")\nx = 1",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = scale (konst y) (one \"USD\")\n  where\n    x = 1\n    y = x\n      * 2",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = let x = 1\n           y = x\n       in scale (konst y) (one \"USD\")",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("main = give\none", &Arena::new()).is_err());
+        assert!(contract(
+            "main = f\n  where f = give\n        (one \"USD\")",
+            &Arena::new()
+        )
+        .is_err());
+
+        let arena = Arena::new();
+        let error = contract(
+            "main = scale\n  (konst 2)\n  (scale (one \"USD\") (one \"USD\"))",
+            &arena,
+        )
+        .unwrap_err();
+        let nom_error = error.nom_error.unwrap();
+        assert_eq!((nom_error.line, nom_error.column), (3, 11));
+    }
+
+    fn modules<'a>(
+        sources: &[&'a str],
+        arena: &'a Arena,
+    ) -> result::Result<Definitions<'a>, Error<'a>> {
+        program(
+            sources
+                .iter()
+                .map(|source| module(source))
+                .collect::<result::Result<_, _>>()?,
+            arena,
+        )
+    }
+
+    #[test]
+    fn parse_prelude() {
+        let arena = Arena::new();
+        let definitions = contract(
+            "main = coupon 2020-12-25T00:00:00Z 5 \"USD\" (zcb 2021-12-25T00:00:00Z 105 \"USD\")",
+            &arena,
+        );
+        let mut names: Vec<_> = definitions
+            .unwrap()
+            .iter()
+            .map(|d| d.variable.name)
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "Prelude_coupon",
+                "Prelude_zcb",
+                "after",
+                "and",
+                "main",
+                "one",
+                "scale"
+            ]
+        );
+
+        assert!(contract(
+            "zcb = one \"USD\"\nmain = and zcb (european 2020-12-25T00:00:00Z zero)",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("main = zcb 2020-12-25T00:00:00Z True", &Arena::new()).is_err());
+        assert!(contract("main = european 10 (one \"USD\")", &Arena::new()).is_err());
+    }
+
+    #[test]
+    fn parse_bond() {
+        assert!(contract(
+            "main = bond 2020-12-25T00:00:00Z 6m 4 5 100 \"USD\"",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            "main = bond 2020-12-25T00:00:00Z 6m 2 5 100 1",
+            &Arena::new()
+        )
+        .is_err());
+        assert_eq!(
+            contract("main t = bond t 6m 4 5 100 \"USD\"", &Arena::new())
+                .unwrap_err()
+                .sprint_error,
+            Some(SprintError::InvalidScheduleError("bond"))
+        );
+        assert_eq!(
+            contract(
+                "main = bond 2020-12-25T00:00:00Z 6m 0 5 100 \"USD\"",
+                &Arena::new()
+            )
+            .unwrap_err()
+            .sprint_error,
+            Some(SprintError::InvalidScheduleError("bond"))
+        );
+    }
+
+    #[test]
+    fn parse_modules() {
+        let arena = Arena::new();
+
+        let bonds = "module Finance.Bonds (zcb) where\n\
+                     helper c = give c\n\
+                     zcb k = scale (konst k) (helper (one \"USD\"))";
+        let options = "module Options where\n\
+                       import Finance.Bonds (zcb)\n\
+                       helper = 2\n\
+                       european = zcb helper";
+
+        let definitions = modules(&[
+            "import Options\nimport Finance.Bonds\nhelper = 3\nmain = and european (zcb helper)",
+            bonds,
+            options,
+        ], &arena)
+        .unwrap();
+
+        let mut names: Vec<_> = definitions.iter().map(|d| d.variable.name).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "Finance_Bonds_helper",
+                "Finance_Bonds_zcb",
+                "Options_european",
+                "Options_helper",
+                "and",
+                "give",
+                "helper",
+                "main",
+                "one",
+                "scale"
+            ]
+        );
+
+        fn error<'a>(sources: &[&'a str], arena: &'a Arena) -> SprintError<'a> {
+            modules(sources, arena).unwrap_err().sprint_error.unwrap()
+        }
+
+        assert_eq!(
+            error(
+                &["import Finance.Bonds (helper)\nmain = zero", bonds],
+                &arena
+            ),
+            SprintError::UnknownExportError("Finance.Bonds", "helper")
+        );
+        assert_eq!(
+            error(
+                &["import Options (zcb)\nmain = zero", bonds, options],
+                &arena
+            ),
+            SprintError::UnknownExportError("Options", "zcb")
+        );
+        assert_eq!(
+            error(
+                &[
+                    "import Finance.Bonds\nimport A\nmain = zero",
+                    bonds,
+                    "module A where\nzcb = zero"
+                ],
+                &arena
+            ),
+            SprintError::DuplicateDefinitionError("zcb")
+        );
+        assert_eq!(
+            error(&["import Bonds\nmain = zero", bonds], &arena),
+            SprintError::UnknownModuleError("Bonds")
+        );
+        assert_eq!(
+            error(
+                &[
+                    "import A\nmain = zero",
+                    "module A where\nimport B",
+                    "module B where\nimport A"
+                ],
+                &arena
+            ),
+            SprintError::CyclicImportError("A")
+        );
+        assert!(matches!(
+            error(
+                &[
+                    "import A\nhelper = 1\nmain = zero",
+                    "module A where\nx = helper"
+                ],
+                &arena
+            ),
+            SprintError::UnknownIdentifierError("helper", _)
+        ));
+        assert!(modules(
+            &["import A\nmain = zero", "module A (x) where\ny = 1"],
+            &arena
+        )
+        .is_err());
+    }
+
+    // Every example in the README compiles, leaving out the signatures of primitives, which are
+    // given without a definition, unless a comment in it expects the kind error it shows. Examples
+    // of modules are compiled along with the example importing them, which follows them.
+    #[test]
+    fn parse_readme() {
+        let arena = Arena::new();
+        let mut imported = Vec::new();
+
+        for example in include_str!("../../../README.md")
+            .split("```haskell\n")
+            .skip(1)
+            .map(|block| block.split("```").next().unwrap())
+        {
+            let defined: Vec<_> = example
+                .lines()
+                .filter(|line| line.contains(" =") && !line.contains(" :: "))
+                .filter_map(|line| line.split_whitespace().next())
+                .collect();
+            let mut source: String = example
+                .lines()
+                .filter(|line| match line.find(" :: ") {
+                    Some(end) => defined.contains(&&line[..end]),
+                    None => true,
+                })
+                .map(|line| format!("{}\n", line))
+                .collect();
+
+            if example.lines().any(|line| line.starts_with("module ")) {
+                imported.push(example);
+                continue;
+            }
+
+            if !defined.contains(&"main") {
+                source.push_str("main = zero\n");
+            }
+
+            let sources: Vec<_> = std::iter::once(source.as_str())
+                .chain(imported.drain(..))
+                .collect();
+            let result = modules(&sources, &arena);
+
+            match example.contains("-- expected") {
+                true => assert!(result.is_err(), "{}", example),
+                false => assert!(result.is_ok(), "{}\n{:?}", example, result.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_lambda() {
+        let apply = "apply :: (Contract -> Contract) -> Contract\napply f = f (one \"USD\")\n";
+        assert!(contract(
+            &format!("{}main = apply (\\c -> give c)", apply),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            &format!("{}main = apply (\\ c -> give (give c))", apply),
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract(
+            &format!("{}main = apply (\\c d -> give c)", apply),
+            &Arena::new()
+        )
+        .is_err());
+        assert!(contract(&format!("{}main = apply (\\c -> 1)", apply), &Arena::new()).is_err());
+        assert!(contract(
+            "f = \\x -> scale (konst x) (one \"USD\")\nmain = f 2",
+            &Arena::new()
+        )
+        .is_ok());
+        assert!(contract("main = scale (konst 2) c\nf = \\c -> c", &Arena::new()).is_err());
     }
 }
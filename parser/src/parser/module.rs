@@ -0,0 +1,35 @@
+use super::{context::Context, Span};
+
+/// The definitions of a single source, whose imports are resolved once the modules they name have
+/// been parsed.
+#[derive(Debug)]
+pub struct Module<'a> {
+    pub name: Option<Span<'a>>,
+    pub imports: Vec<Import<'a>>,
+    /// The names visible to importing modules, or all definitions if there is no export list.
+    pub(super) exports: Option<Vec<Span<'a>>>,
+    pub(super) context: Context<'a, ()>,
+}
+
+#[derive(Debug)]
+pub struct Import<'a> {
+    pub module: Span<'a>,
+    /// The names brought into scope, or all of those exported if there is no import list.
+    pub names: Option<Vec<Span<'a>>>,
+}
+
+impl<'a> Module<'a> {
+    pub fn new(
+        name: Option<Span<'a>>,
+        exports: Option<Vec<Span<'a>>>,
+        imports: Vec<Import<'a>>,
+        context: Context<'a, ()>,
+    ) -> Self {
+        Self {
+            name,
+            imports,
+            exports,
+            context,
+        }
+    }
+}
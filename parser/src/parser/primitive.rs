@@ -1,31 +1,48 @@
 #![allow(unused_parens)]
 
 use super::{
-    builder::definition, context::Context, error::SprintError, unify::Unify, Error, Result, Span,
+    builder::{application, binary, definition},
+    context::Context,
+    error::SprintError,
+    unify::Unify,
+    Error, Span,
 };
 use crate::ast::{
     state::{Effect, State, Transition},
-    {Class, Comparable, Date, Expression, ExpressionType, Kind, Observable, Variable},
+    {
+        Boolean, Class, Comparable, Date, Expression, ExpressionType, Kind, Observable, Schedule,
+        Statistic, Variable,
+    },
 };
-use nom::Err;
 use phf::phf_map;
+use std::{rc::Rc, result};
 
-type Primitive = fn(Vec<Expression>) -> Context<Expression>;
+type Primitive = fn(Vec<Expression>) -> result::Result<Context<Expression>, Error>;
 
 pub static PRIMITIVES: phf::Map<&'static str, Primitive> = phf_map! {
+    "amount" => amount,
+    "average" => average,
+    "bond" => bond,
+    "isRole" => is_role,
     "konst" => konst,
+    "lift" => lift,
+    "lift2" => lift2,
+    "max" => max,
+    "maxSince" => max_since,
+    "min" => min,
+    "minSince" => min_since,
+    "not" => not,
 };
 
 macro_rules! arguments {
     ($arguments:expr $(, $kind:expr)*) => {
         {
             let mut arguments = $arguments.into_iter();
-            let expressions = ($(argument(&mut arguments, $kind)),*);
+            let expressions = ($(argument(&mut arguments, $kind)?),*);
 
-            assert!(
-                arguments.next().is_none(),
-                "invalid number of arguments in primitive application"
-            );
+            if arguments.next().is_some() {
+                return Err(Error::from_sprint_error(SprintError::InvalidNumberArgsError, None));
+            }
 
             expressions
         }
@@ -42,30 +59,47 @@ pub fn zero() -> Context<'static, Expression<'static>> {
 }
 
 pub fn one() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let currency = Expression::new(
+        ExpressionType::from(Variable::new(
+            "currency",
+            Kind::Currency(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
     let mut transition = Transition::default();
-    transition.add_effect(Effect::Withdraw);
+    transition.add_effect(Effect::Withdraw(currency));
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
         Span::new("one"),
-        vec![],
+        vec![Span::new("currency")],
         Expression::new(ExpressionType::from(state), None).into(),
     )
     .unwrap()
 }
 
 pub fn give() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
     let next = Expression::new(
-        ExpressionType::from(Variable::new("next", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
         None,
     );
 
     let mut transition = Transition::default();
     transition.add_effect(Effect::Flip).set_next(next);
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
@@ -76,14 +110,76 @@ pub fn give() -> Context<'static, Expression<'static>> {
     .unwrap()
 }
 
+pub fn give_to() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let role = Expression::new(
+        ExpressionType::from(Variable::new("role", Kind::Role.into(), None)),
+        None,
+    );
+
+    let next = Expression::new(
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let mut transition = Transition::default();
+    transition.add_effect(Effect::GiveTo(role)).set_next(next);
+
+    let mut state = State::with_unit(unit);
+    state.add_transition(transition);
+
+    definition(
+        Span::new("giveTo"),
+        vec![Span::new("role"), Span::new("next")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
+pub fn pay_to() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let payee = Expression::new(
+        ExpressionType::from(Variable::new("payee", Kind::Address.into(), None)),
+        None,
+    );
+
+    let next = Expression::new(
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let mut transition = Transition::default();
+    transition.add_effect(Effect::PayTo(payee)).set_next(next);
+
+    let mut state = State::with_unit(unit);
+    state.add_transition(transition);
+
+    definition(
+        Span::new("payTo"),
+        vec![Span::new("payee"), Span::new("next")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
 pub fn and() -> Context<'static, Expression<'static>> {
     let left = Expression::new(
-        ExpressionType::from(Variable::new("left", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new("left", contract(), None)),
         None,
     );
 
     let right = Expression::new(
-        ExpressionType::from(Variable::new("right", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new("right", contract(), None)),
         None,
     );
 
@@ -103,11 +199,11 @@ pub fn and() -> Context<'static, Expression<'static>> {
 
 pub fn or() -> Context<'static, Expression<'static>> {
     let left = Expression::new(
-        ExpressionType::from(Variable::new("left", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new("left", contract(), None)),
         None,
     );
     let right = Expression::new(
-        ExpressionType::from(Variable::new("right", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new("right", contract(), None)),
         None,
     );
 
@@ -134,7 +230,56 @@ pub fn or() -> Context<'static, Expression<'static>> {
     .unwrap()
 }
 
+pub fn cond() -> Context<'static, Expression<'static>> {
+    let condition = Expression::new(
+        ExpressionType::from(Variable::new(
+            "condition",
+            Kind::Observable(Kind::Boolean.into()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let left = Expression::new(
+        ExpressionType::from(Variable::new("left", contract(), None)),
+        None,
+    );
+    let right = Expression::new(
+        ExpressionType::from(Variable::new("right", contract(), None)),
+        None,
+    );
+
+    let otherwise = Expression::new(
+        ExpressionType::Class(Class::Boolean(Boolean::Not(condition.clone().into()))),
+        None,
+    );
+
+    let mut left_transition = Transition::default();
+    left_transition.add_condition(condition).set_next(left);
+
+    let mut right_transition = Transition::default();
+    right_transition.add_condition(otherwise).set_next(right);
+
+    let mut state = State::default();
+    state
+        .add_transition(left_transition)
+        .add_transition(right_transition);
+
+    definition(
+        Span::new("cond"),
+        vec![
+            Span::new("condition"),
+            Span::new("left"),
+            Span::new("right"),
+        ],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
 pub fn before() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
     let now = Expression::new(ExpressionType::Date(Date::Now), None);
 
     let date = Expression::new(
@@ -143,7 +288,11 @@ pub fn before() -> Context<'static, Expression<'static>> {
     );
 
     let next = Expression::new(
-        ExpressionType::from(Variable::new("next", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
         None,
     );
 
@@ -155,7 +304,7 @@ pub fn before() -> Context<'static, Expression<'static>> {
         ))
         .set_next(next);
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
@@ -167,6 +316,8 @@ pub fn before() -> Context<'static, Expression<'static>> {
 }
 
 pub fn after() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
     let now = Expression::new(ExpressionType::Date(Date::Now), None);
 
     let date = Expression::new(
@@ -175,7 +326,11 @@ pub fn after() -> Context<'static, Expression<'static>> {
     );
 
     let next = Expression::new(
-        ExpressionType::from(Variable::new("next", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
         None,
     );
 
@@ -190,7 +345,7 @@ pub fn after() -> Context<'static, Expression<'static>> {
         ))
         .set_next(next);
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
@@ -201,25 +356,228 @@ pub fn after() -> Context<'static, Expression<'static>> {
     .unwrap()
 }
 
+pub fn truncate() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let now = Expression::new(ExpressionType::Date(Date::Now), None);
+
+    let date = Expression::new(
+        ExpressionType::from(Variable::new("date", Kind::Date.into(), None)),
+        None,
+    );
+
+    let next = Expression::new(
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let expired = Expression::new(
+        ExpressionType::Class(Class::Comparable(Comparable::Greater(
+            now.clone().into(),
+            date.clone().into(),
+        ))),
+        None,
+    );
+
+    // The inner contract is guarded by the date as well, so that none of its choices can be made
+    // after it.
+    let mut transition = Transition::default();
+    transition
+        .add_condition(Expression::new(
+            ExpressionType::Class(Class::Comparable(Comparable::LessEqual(
+                now.into(),
+                date.into(),
+            ))),
+            None,
+        ))
+        .add_effect(Effect::Guard(expired))
+        .set_next(next);
+
+    let mut state = State::with_unit(unit);
+    state.add_transition(transition);
+
+    definition(
+        Span::new("truncate"),
+        vec![Span::new("date"), Span::new("next")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
+pub fn then() -> Context<'static, Expression<'static>> {
+    let now = Expression::new(ExpressionType::Date(Date::Now), None);
+
+    let first = Expression::new(
+        ExpressionType::from(Variable::new("first", contract(), None)),
+        None,
+    );
+    let second = Expression::new(
+        ExpressionType::from(Variable::new("second", contract(), None)),
+        None,
+    );
+
+    let horizon = |contract: &Expression<'static>| {
+        Box::new(Expression::new(
+            ExpressionType::Date(Date::Horizon(contract.clone().into())),
+            None,
+        ))
+    };
+
+    let mut first_transition = Transition::default();
+    first_transition
+        .add_condition(Expression::new(
+            ExpressionType::Class(Class::Comparable(Comparable::LessEqual(
+                now.clone().into(),
+                horizon(&first),
+            ))),
+            None,
+        ))
+        .set_next(first.clone());
+
+    // The second contract is acquired once the first one has expired, until its own horizon.
+    let mut second_transition = Transition::default();
+    second_transition
+        .add_condition(Expression::new(
+            ExpressionType::Class(Class::Comparable(Comparable::Greater(
+                now.clone().into(),
+                horizon(&first),
+            ))),
+            None,
+        ))
+        .add_condition(Expression::new(
+            ExpressionType::Class(Class::Comparable(Comparable::LessEqual(
+                now.into(),
+                horizon(&second),
+            ))),
+            None,
+        ))
+        .set_next(second);
+
+    let mut state = State::default();
+    state
+        .add_transition(first_transition)
+        .add_transition(second_transition);
+
+    definition(
+        Span::new("then"),
+        vec![Span::new("first"), Span::new("second")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
+pub fn when() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let condition = Expression::new(
+        ExpressionType::from(Variable::new(
+            "condition",
+            Kind::Observable(Kind::Boolean.into()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let next = Expression::new(
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let mut transition = Transition::default();
+    transition.add_condition(condition).set_next(next);
+
+    let mut state = State::with_unit(unit);
+    state.add_transition(transition);
+
+    definition(
+        Span::new("when"),
+        vec![Span::new("condition"), Span::new("next")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
+pub fn until() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
+    let condition = Expression::new(
+        ExpressionType::from(Variable::new(
+            "condition",
+            Kind::Observable(Kind::Boolean.into()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let next = Expression::new(
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
+        None,
+    );
+
+    let otherwise = Expression::new(
+        ExpressionType::Class(Class::Boolean(Boolean::Not(condition.clone().into()))),
+        None,
+    );
+
+    // The inner contract is guarded by the condition, so that it can be abandoned at any point
+    // after it has been acquired.
+    let mut acquire = Transition::default();
+    acquire
+        .add_condition(otherwise)
+        .add_effect(Effect::Guard(condition.clone()))
+        .set_next(next);
+
+    let mut abandon = Transition::default();
+    abandon.add_condition(condition);
+
+    let mut state = State::with_unit(unit);
+    state.add_transition(acquire).add_transition(abandon);
+
+    definition(
+        Span::new("until"),
+        vec![Span::new("condition"), Span::new("next")],
+        Expression::new(ExpressionType::from(state), None).into(),
+    )
+    .unwrap()
+}
+
 pub fn scale() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
     let scalar = Expression::new(
         ExpressionType::from(Variable::new(
             "scalar",
-            Kind::Observable(Kind::Word.into()).into(),
+            Kind::Observable(Kind::Decimal.into()).into(),
             None,
         )),
         None,
     );
 
     let next = Expression::new(
-        ExpressionType::from(Variable::new("next", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
         None,
     );
 
     let mut transition = Transition::default();
     transition.add_effect(Effect::Scale(scalar)).set_next(next);
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
@@ -231,8 +589,14 @@ pub fn scale() -> Context<'static, Expression<'static>> {
 }
 
 pub fn anytime() -> Context<'static, Expression<'static>> {
+    let unit = Rc::new(Kind::default());
+
     let next = Expression::new(
-        ExpressionType::from(Variable::new("next", Kind::State.into(), None)),
+        ExpressionType::from(Variable::new(
+            "next",
+            Kind::State(unit.clone()).into(),
+            None,
+        )),
         None,
     );
 
@@ -241,7 +605,7 @@ pub fn anytime() -> Context<'static, Expression<'static>> {
         .add_condition(Expression::new(Observable::IsParty.into(), next.span))
         .set_next(next);
 
-    let mut state = State::default();
+    let mut state = State::with_unit(unit);
     state.add_transition(transition);
 
     definition(
@@ -252,30 +616,246 @@ pub fn anytime() -> Context<'static, Expression<'static>> {
     .unwrap()
 }
 
-pub fn konst(arguments: Vec<Expression>) -> Context<Expression> {
-    let value = arguments!(arguments, Kind::default()).unwrap();
+pub fn konst(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let value = arguments!(arguments, Kind::default());
+
+    Ok(Expression::new(ExpressionType::Observable(value.clone().into()), value.span).into())
+}
+
+pub fn is_role(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let role = arguments!(arguments, Kind::Role);
+    let span = role.span;
+
+    Ok(Expression::new(Observable::IsRole(role.into()).into(), span).into())
+}
+
+// Amounts are decimals of the currency they are given with, which only multiplying them by an
+// exchange rate converts to another.
+pub fn amount(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let unit = Rc::new(Kind::default());
+    let (currency, value) = arguments!(arguments, Kind::Currency(unit.clone()), Kind::Decimal);
+    let span = currency.span;
+
+    Ok(Expression::new(ExpressionType::Amount(unit, value.into()), span).into())
+}
+
+pub fn lift(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let (from, to) = (Rc::new(Kind::default()), Rc::new(Kind::default()));
+    let (f, value) = arguments!(
+        arguments,
+        Kind::Abstraction(from.clone(), to),
+        Kind::Observable(from)
+    );
+    let span = f.span;
+
+    Ok(Expression::new(
+        ExpressionType::Observable(Observable::Lift(f.into(), vec![value.into()])),
+        span,
+    )
+    .into())
+}
+
+pub fn lift2(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let (left, right) = (Rc::new(Kind::default()), Rc::new(Kind::default()));
+    let (f, left_value, right_value) = arguments!(
+        arguments,
+        Kind::Abstraction(
+            left.clone(),
+            Kind::Abstraction(right.clone(), Kind::default().into()).into()
+        ),
+        Kind::Observable(left),
+        Kind::Observable(right)
+    );
+    let span = f.span;
+
+    Ok(Expression::new(
+        ExpressionType::Observable(Observable::Lift(
+            f.into(),
+            vec![left_value.into(), right_value.into()],
+        )),
+        span,
+    )
+    .into())
+}
+
+pub fn max(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let (left, right) = arguments!(arguments, observable_word(), observable_word());
+    let span = left.span;
+
+    Ok(Expression::new(
+        ExpressionType::Observable(Observable::Max(left.into(), right.into())),
+        span,
+    )
+    .into())
+}
+
+pub fn min(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let (left, right) = arguments!(arguments, observable_word(), observable_word());
+    let span = left.span;
+
+    Ok(Expression::new(
+        ExpressionType::Observable(Observable::Min(left.into(), right.into())),
+        span,
+    )
+    .into())
+}
+
+pub fn average(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    observed("average", Statistic::Average, arguments)
+}
+
+pub fn max_since(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    observed("maxSince", Statistic::Maximum, arguments)
+}
+
+pub fn min_since(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    observed("minSince", Statistic::Minimum, arguments)
+}
+
+// Observations are recorded on a schedule which is known when the contract is generated, so it
+// must be written literally, and its last date must be in range.
+fn observed<'a>(
+    name: &'static str,
+    statistic: Statistic,
+    arguments: Vec<Expression<'a>>,
+) -> result::Result<Context<'a, Expression<'a>>, Error<'a>> {
+    let (start, period, count, value) = arguments!(
+        arguments,
+        Kind::Date,
+        Kind::Duration,
+        Kind::Word,
+        observable_word()
+    );
+
+    let schedule = schedule(name, start, period, count)?;
+    let span = value.span;
+
+    Ok(Expression::new(
+        ExpressionType::Observable(Observable::Observed(statistic, schedule, value.into())),
+        span,
+    )
+    .into())
+}
+
+// A bond paying a fixed coupon at each date of a schedule, and its notional at the last. The
+// schedule is written literally like that of a statistic, so that the bond is unrolled into a
+// payment after each date when it is parsed.
+pub fn bond<'a>(
+    arguments: Vec<Expression<'a>>,
+) -> result::Result<Context<'a, Expression<'a>>, Error<'a>> {
+    let (start, period, count, coupon, notional, currency) = arguments!(
+        arguments,
+        Kind::Date,
+        Kind::Duration,
+        Kind::Word,
+        Kind::Word,
+        Kind::Word,
+        Kind::Currency(Kind::default().into())
+    );
+
+    let schedule = schedule("bond", start, period, count)?;
+
+    let payment = |i: u64, amount: Context<'a, Expression<'a>>| {
+        let date = schedule
+            .period
+            .times(i)
+            .and_then(|duration| duration.after(schedule.start))
+            .unwrap();
+        let date = Expression::new(ExpressionType::Date(Date::Date(date)), None);
+
+        let scalar = apply("konst", vec![amount]);
+        let one = apply("one", vec![currency.clone().into()]);
+        apply(
+            "after",
+            vec![date.into(), apply("scale", vec![scalar, one])],
+        )
+    };
+
+    let last = binary(Span::new("+"), coupon.clone().into(), notional.into())
+        .unwrap_or_else(|_| unreachable!("words can be added"));
+
+    Ok((0..schedule.count - 1)
+        .rev()
+        .fold(payment(schedule.count - 1, last), |rest, i| {
+            apply("and", vec![payment(i, coupon.clone().into()), rest])
+        }))
+}
+
+// The schedule of a primitive, from its first date, the duration between its dates and their
+// number, which must be written literally, and whose last date must be in range.
+fn schedule<'a>(
+    name: &'static str,
+    start: Expression<'a>,
+    period: Expression<'a>,
+    count: Expression<'a>,
+) -> result::Result<Schedule, Error<'a>> {
+    match (start.expression, period.expression, count.expression) {
+        (
+            ExpressionType::Date(Date::Date(start)),
+            ExpressionType::Duration(period),
+            ExpressionType::Word(count),
+        ) if period.word() != Some(0)
+            && count > 0
+            && period
+                .times(count)
+                .and_then(|duration| duration.after(start))
+                .is_some() =>
+        {
+            Ok(Schedule {
+                start,
+                period,
+                count,
+            })
+        }
+        _ => Err(Error::from_sprint_error(
+            SprintError::InvalidScheduleError(name),
+            None,
+        )),
+    }
+}
+
+pub fn not(arguments: Vec<Expression>) -> result::Result<Context<Expression>, Error> {
+    let value = arguments!(arguments, Kind::default());
+
+    Ok(Expression::new(
+        ExpressionType::Class(Class::Boolean(Boolean::Not(value.clone().into()))),
+        value.span,
+    )
+    .into())
+}
+
+// A contract paying in a unit of its own, such as either of those combined by `and`.
+fn contract() -> Rc<Kind> {
+    Kind::State(Kind::default().into()).into()
+}
+
+// Applies a primitive to arguments which are known to be of its kinds.
+fn apply<'a>(
+    name: &'static str,
+    arguments: Vec<Context<'a, Expression<'a>>>,
+) -> Context<'a, Expression<'a>> {
+    application(Span::new(name), arguments)
+        .unwrap_or_else(|_| unreachable!("\"{}\" is applied to arguments of its kinds", name))
+}
 
-    Expression::new(ExpressionType::Observable(value.clone().into()), value.span).into()
+fn observable_word() -> Kind {
+    Kind::Observable(Kind::Word.into())
 }
 
 fn argument<'a>(
     arguments: &mut impl Iterator<Item = Expression<'a>>,
     kind: Kind,
-) -> Result<'a, Expression<'a>> {
+) -> result::Result<Expression<'a>, Error<'a>> {
     let argument = match arguments.next() {
         Some(argument) => argument,
         None => {
-            return Err(Err::Error(Error::from_sprint_error(
+            return Err(Error::from_sprint_error(
                 SprintError::InvalidNumberArgsError,
                 None,
-            )));
+            ));
         }
     };
-    argument
-        .expression
-        .kind()
-        .unify(kind.into())
-        .map_err(Err::Error)?;
+    Rc::new(kind).unify(argument.expression.kind())?;
 
     Ok(argument)
 }
@@ -1,112 +1,516 @@
 use super::{
     builder,
-    combinator::{brackets1, padding0},
+    combinator::{brackets1, comment, padding0, whitespace0, whitespace1},
     context::Context,
-    date::date,
-    IResult, Span,
+    date::{date, duration},
+    error::SprintError,
+    module::{Import, Module},
+    Error, IResult, Span,
 };
-use crate::ast::{Expression, ExpressionType, Kind};
+use crate::ast::{Decimal, Expression, ExpressionType, Kind, Observable};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
-    character::complete::{alphanumeric0, digit1, line_ending, multispace1, space1},
-    combinator::{map, map_res, peek, recognize},
-    multi::{many0, many1, separated_list},
-    sequence::{pair, preceded, separated_pair},
+    character::complete::{
+        alphanumeric0, alphanumeric1, digit1, hex_digit1, line_ending, one_of, space0, space1,
+    },
+    combinator::{map, map_res, not, opt, peek, recognize, verify},
+    multi::{many0, many1, separated_list, separated_nonempty_list},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    Err, Offset, Slice,
 };
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-pub fn program(input: Span) -> IResult<Span, Context<()>> {
-    let separator = alt((line_ending, padding0(tag(";"))));
-    let (input, contexts) = separated_list(many1(separator), alt((signature, definition)))(input)?;
-    let (input, _) = many0(alt((multispace1, tag(";"))))(input)?;
+pub fn module(input: Span) -> IResult<Span, Module> {
+    let separator = alt((preceded(blank0, line_ending), padding0(tag(";"))));
+    let (input, _) = whitespace0(input)?;
+    let (input, header) = opt(terminated(header, many0(&separator)))(input)?;
+    let (input, imports) = many0(terminated(import, many0(&separator)))(input)?;
+    let (input, contexts) =
+        separated_list(many1(&separator), alt((roles, signature, definition)))(input)?;
+    let (input, _) = many0(alt((whitespace1, tag(";"))))(input)?;
 
-    Ok((input, builder::program(contexts)?))
+    let (name, exports) = match header {
+        Some((name, exports)) => (Some(name), exports),
+        None => (None, None),
+    };
+
+    Ok((input, builder::module(name, exports, imports, contexts)?))
+}
+
+fn header(input: Span) -> IResult<Span, (Span, Option<Vec<Span>>)> {
+    let (input, _) = terminated(keyword("module"), whitespace1)(input)?;
+    let (input, name) = module_name(input)?;
+    let (input, exports) = opt(preceded(whitespace0, names))(input)?;
+    let (input, _) = preceded(whitespace0, keyword("where"))(input)?;
+
+    Ok((input, (name, exports)))
+}
+
+fn import(input: Span) -> IResult<Span, Import> {
+    let (input, _) = terminated(keyword("import"), whitespace1)(input)?;
+    let (input, module) = module_name(input)?;
+    let (input, names) = opt(preceded(blank0, names))(input)?;
+
+    Ok((input, Import { module, names }))
+}
+
+// Module names are capitalised, and can be nested using dots, e.g. `Finance.Bonds`.
+fn module_name(input: Span) -> IResult<Span, Span> {
+    let uppercase1 = take_while1(|c: char| c.is_ascii_uppercase());
+    recognize(separated_nonempty_list(
+        tag("."),
+        pair(uppercase1, alphanumeric0),
+    ))(input)
+}
+
+fn names(input: Span) -> IResult<Span, Vec<Span>> {
+    let names = separated_list(tag(","), padding0(identifier));
+    delimited(tag("("), terminated(names, whitespace0), tag(")"))(input)
+}
+
+// Roles are declared as their names after `roles`, such as `roles lender borrower agent`.
+fn roles(input: Span) -> IResult<Span, Context<Expression>> {
+    let (input, keyword) = terminated(keyword("roles"), space1)(input)?;
+    let (input, names) = separated_nonempty_list(space1, identifier)(input)?;
+
+    Ok((input, builder::roles(keyword, names)?))
 }
 
 pub fn signature(input: Span) -> IResult<Span, Context<Expression>> {
     let (input, identifier) = identifier(input)?;
     let (input, _) = padding0(tag("::"))(input)?;
-    let (input, kind) = kind(input)?;
+    let (input, kind) = kind(input, &Default::default())?;
 
     Ok((input, builder::signature(identifier, kind)?))
 }
 
-pub fn kind(input: Span) -> IResult<Span, Kind> {
+// Kind variables with the same name refer to the same kind within a signature.
+type KindVariables<'a> = RefCell<HashMap<&'a str, Rc<Kind>>>;
+
+pub fn kind<'a>(input: Span<'a>, variables: &KindVariables<'a>) -> IResult<'a, Span<'a>, Rc<Kind>> {
     alt((
         map(
-            separated_pair(kind_primitive, padding0(tag("->")), kind),
-            |(from, to)| Kind::Abstraction(from.into(), to.into()),
+            separated_pair(
+                |input| kind_primitive(input, variables),
+                padding0(tag("->")),
+                |input| kind(input, variables),
+            ),
+            |(from, to)| Kind::Abstraction(from, to).into(),
         ),
-        kind_primitive,
+        |input| kind_primitive(input, variables),
     ))(input)
 }
 
-pub fn kind_primitive(input: Span) -> IResult<Span, Kind> {
+pub fn kind_primitive<'a>(
+    input: Span<'a>,
+    variables: &KindVariables<'a>,
+) -> IResult<'a, Span<'a>, Rc<Kind>> {
     alt((
-        brackets1(kind),
-        map(tag("Bool"), |_| Kind::Boolean),
-        map(preceded(pair(tag("Observable"), separator), kind), |k| {
-            Kind::Observable(k.into())
+        brackets1(|input| kind(input, variables)),
+        map(tag("Address"), |_| Kind::Address.into()),
+        map(tag("Bool"), |_| Kind::Boolean.into()),
+        map(
+            preceded(pair(tag("Observable"), separator(TOP_LEVEL)), |input| {
+                kind_primitive(input, variables)
+            }),
+            |k| Kind::Observable(k).into(),
+        ),
+        map(
+            preceded(pair(tag("Amount"), separator(TOP_LEVEL)), |input| {
+                unit(input, variables)
+            }),
+            |unit| Kind::Amount(unit).into(),
+        ),
+        map(
+            preceded(
+                pair(tag("Rate"), separator(TOP_LEVEL)),
+                separated_pair(
+                    |input| unit(input, variables),
+                    separator(TOP_LEVEL),
+                    |input| unit(input, variables),
+                ),
+            ),
+            |(from, to)| Kind::Rate(from, to).into(),
+        ),
+        map(
+            preceded(
+                tag("Contract"),
+                opt(preceded(separator(TOP_LEVEL), |input| {
+                    unit(input, variables)
+                })),
+            ),
+            |unit| Kind::State(unit.unwrap_or_default()).into(),
+        ),
+        map(
+            preceded(
+                tag("Currency"),
+                opt(preceded(separator(TOP_LEVEL), |input| {
+                    unit(input, variables)
+                })),
+            ),
+            |unit| Kind::Currency(unit.unwrap_or_default()).into(),
+        ),
+        map(tag("Date"), |_| Kind::Date.into()),
+        map(tag("Decimal"), |_| Kind::Decimal.into()),
+        map(tag("Duration"), |_| Kind::Duration.into()),
+        map(tag("Role"), |_| Kind::Role.into()),
+        map(tag("Word"), |_| Kind::Word.into()),
+        map(identifier, |name| {
+            variables
+                .borrow_mut()
+                .entry(name.fragment)
+                .or_default()
+                .clone()
+        }),
+    ))(input)
+}
+
+// Units are currencies, written as their names such as `USD`, or variables. The units of contracts
+// and currencies can be left out, in which case they are inferred.
+fn unit<'a>(input: Span<'a>, variables: &KindVariables<'a>) -> IResult<'a, Span<'a>, Rc<Kind>> {
+    let uppercase1 = take_while1(|c: char| c.is_ascii_uppercase());
+
+    alt((
+        map(recognize(pair(uppercase1, alphanumeric0)), |name: Span| {
+            Kind::Unit(name.fragment.to_string()).into()
+        }),
+        map(identifier, |name| {
+            variables
+                .borrow_mut()
+                .entry(name.fragment)
+                .or_default()
+                .clone()
         }),
-        map(tag("Contract"), |_| Kind::State),
-        map(tag("Word"), |_| Kind::Word),
     ))(input)
 }
 
 pub fn definition(input: Span) -> IResult<Span, Context<Expression>> {
     let (input, id) = identifier(input)?;
 
-    let argument = preceded(multispace1, identifier);
+    let argument = preceded(whitespace1, identifier);
     let (input, arguments) = many0(argument)(input)?;
 
     let (input, _) = padding0(tag("="))(input)?;
-    let (input, expression) = expression(input)?;
+    let (input, expression) = expression(input, TOP_LEVEL)?;
+
+    let (input, expression) = match opt(preceded(padding0(keyword("where")), bindings))(input)? {
+        (input, Some(bindings)) => (input, builder::bindings(bindings, expression)?),
+        (input, None) => (input, expression),
+    };
 
     Ok((input, builder::definition(id, arguments, expression)?))
 }
 
-pub fn expression(input: Span) -> IResult<Span, Context<Expression>> {
-    alt((application, term))(input)
+// Top-level definitions start in the first column, so any indented line continues them.
+const TOP_LEVEL: usize = 1;
+
+// Expressions continue onto following lines which are indented further than the column of their
+// layout, i.e. that of the definition or binding they are part of.
+pub fn expression(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    alt((
+        |input| local(input, layout),
+        |input| lambda(input, layout),
+        |input| disjunction(input, layout),
+    ))(input)
+}
+
+fn lambda(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let (input, _) = terminated(tag("\\"), whitespace0)(input)?;
+    let (input, arguments) = separated_nonempty_list(whitespace1, identifier)(input)?;
+    let (input, _) = padding0(tag("->"))(input)?;
+    let (input, expression) = expression(input, layout)?;
+
+    Ok((input, builder::abstraction(arguments, expression)?))
+}
+
+fn local(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let (input, _) = terminated(keyword("let"), whitespace1)(input)?;
+    let (input, bindings) = bindings(input)?;
+    let (input, _) = padding0(keyword("in"))(input)?;
+    let (input, expression) = expression(input, layout)?;
+
+    Ok((input, builder::bindings(bindings, expression)?))
+}
+
+// Bindings are separated by semicolons, or by new lines indented to the column of the first one.
+fn bindings(input: Span) -> IResult<Span, Vec<(Span, Context<Expression>)>> {
+    let layout = input.get_column();
+    let separator = alt((padding0(tag(";")), newline(move |column| column == layout)));
+    let binding = separated_pair(identifier, padding0(tag("=")), move |input| {
+        expression(input, layout)
+    });
+
+    separated_nonempty_list(separator, binding)(input)
+}
+
+// Operators follow the precedence and associativity of their Haskell counterparts, binding less
+// tightly than function application.
+fn disjunction(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    infix(
+        input,
+        |input| conjunction(input, layout),
+        operator("||"),
+        |input| disjunction(input, layout),
+    )
+}
+
+fn conjunction(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    infix(
+        input,
+        |input| comparison(input, layout),
+        operator("&&"),
+        |input| conjunction(input, layout),
+    )
+}
+
+fn comparison(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let comparator = alt((
+        operator("=="),
+        operator("/="),
+        operator("<="),
+        operator("<"),
+        operator(">="),
+        operator(">"),
+    ));
+
+    // Comparisons are non-associative, so at most one may appear without brackets.
+    let sum = |input| sum(input, layout);
+    infix(input, sum, comparator, sum)
+}
+
+fn sum(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let product = |input| product(input, layout);
+    let (rest, first) = alt((|input| negation(input, layout), product))(input)?;
+    infixl(
+        input,
+        rest,
+        first,
+        alt((operator("+"), operator("-"))),
+        product,
+    )
 }
 
-pub fn application(input: Span) -> IResult<Span, Context<Expression>> {
+fn negation(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let (rest, operator) = terminated(operator("-"), whitespace0)(input)?;
+    let (rest, operand) = product(rest, layout)?;
+    let negation = builder::negation(operator, operand)?;
+
+    Ok((rest, builder::fold(consumed(input, rest), negation)?))
+}
+
+fn product(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    let operand = |input| operand(input, layout);
+    let (rest, first) = operand(input)?;
+    infixl(
+        input,
+        rest,
+        first,
+        alt((operator("*"), operator("/"))),
+        operand,
+    )
+}
+
+fn operand(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
+    alt((
+        |input| application(input, layout),
+        |input| term(input, layout),
+    ))(input)
+}
+
+fn infixl<'a>(
+    start: Span<'a>,
+    mut input: Span<'a>,
+    first: Context<'a, Expression<'a>>,
+    operator: impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>>,
+    operand: impl Fn(Span<'a>) -> IResult<Span<'a>, Context<'a, Expression<'a>>>,
+) -> IResult<'a, Span<'a>, Context<'a, Expression<'a>>> {
+    let mut context = first;
+
+    // Each operation is folded along with the operations to its left, which it includes.
+    while let (rest, Some((operator, right))) = opt(pair(padding0(&operator), &operand))(input)? {
+        let binary = builder::binary(operator, context, right)?;
+        context = builder::fold(consumed(start, rest), binary)?;
+        input = rest;
+    }
+
+    Ok((input, context))
+}
+
+// The part of the input which was parsed before the rest of it.
+fn consumed<'a>(input: Span<'a>, rest: Span<'a>) -> Span<'a> {
+    input.slice(..input.offset(&rest))
+}
+
+// Parses an optional right operand. Operators are right-associative if `right` recurses.
+fn infix<'a>(
+    input: Span<'a>,
+    left: impl Fn(Span<'a>) -> IResult<Span<'a>, Context<'a, Expression<'a>>>,
+    operator: impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>>,
+    right: impl Fn(Span<'a>) -> IResult<Span<'a>, Context<'a, Expression<'a>>>,
+) -> IResult<'a, Span<'a>, Context<'a, Expression<'a>>> {
+    let (input, left) = left(input)?;
+    match opt(pair(padding0(operator), right))(input)? {
+        (input, Some((operator, right))) => Ok((input, builder::binary(operator, left, right)?)),
+        (input, None) => Ok((input, left)),
+    }
+}
+
+pub fn application(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
     let (input, identifier) = identifier(input)?;
 
-    let argument = preceded(separator, term);
+    let argument = preceded(separator(layout), |input| term(input, layout));
     let (input, arguments) = many0(argument)(input)?;
     Ok((input, builder::application(identifier, arguments)?))
 }
 
-pub fn term(input: Span) -> IResult<Span, Context<Expression>> {
+pub fn term(input: Span, layout: usize) -> IResult<Span, Context<Expression>> {
     alt((
-        brackets1(expression),
+        brackets1(move |input| expression(input, layout)),
+        map(
+            preceded(pair(keyword("obs"), separator(layout)), string),
+            |name| Expression::new(Observable::Named(name.fragment).into(), Some(name)).into(),
+        ),
+        map(
+            preceded(
+                pair(keyword("fx"), separator(layout)),
+                separated_pair(currency, separator(layout), currency),
+            ),
+            |(from, to)| {
+                let exchange = Observable::Exchange(from.fragment, to.fragment);
+                Expression::new(exchange.into(), Some(from)).into()
+            },
+        ),
         map_res(identifier, |identifier| {
             builder::application(identifier, Vec::new())
         }),
+        map(currency, |name| {
+            Expression::new(ExpressionType::Currency(name.fragment), Some(name)).into()
+        }),
+        map(address, Context::from),
+        map(literal, Context::from),
+    ))(input)
+}
+
+/// Parses a boolean, date, duration, decimal or word, which are the literals that can be given as
+/// the arguments of `main`.
+pub fn literal(input: Span) -> IResult<Span, Expression> {
+    alt((
         map(tag("True"), |span| {
-            Expression::new(ExpressionType::from(true), Some(span)).into()
+            Expression::new(ExpressionType::from(true), Some(span))
         }),
         map(tag("False"), |span| {
-            Expression::new(ExpressionType::from(false), Some(span)).into()
-        }),
-        map(date, Context::from),
-        map(digit1, |n: Span| {
-            Expression::new(
-                ExpressionType::from(n.fragment.parse::<u64>().unwrap()),
-                Some(n),
-            )
-            .into()
+            Expression::new(ExpressionType::from(false), Some(span))
         }),
+        date,
+        duration,
+        decimal,
+        word,
     ))(input)
 }
 
+// Currencies are written as their names in quotes, such as `"USD"`.
+fn currency(input: Span) -> IResult<Span, Span> {
+    delimited(tag("\""), alphanumeric1, tag("\""))(input)
+}
+
+// Addresses are written as hexadecimal digits after `0x`, such as `0x2a`, and must fit in 32 bytes.
+fn address(input: Span) -> IResult<Span, Expression> {
+    let (input, span) = recognize(preceded(tag("0x"), hex_digit1))(input)?;
+
+    if span.fragment.len() > 66 {
+        return Err(out_of_range(span, Kind::Address));
+    }
+
+    Ok((
+        input,
+        Expression::new(ExpressionType::Address(span.fragment), Some(span)),
+    ))
+}
+
+// Decimals are written with digits on both sides of the point, such as `0.025`.
+fn decimal(input: Span) -> IResult<Span, Expression> {
+    let (input, span) = recognize(separated_pair(digit1, tag("."), digit1))(input)?;
+    let (whole, fraction) = span.fragment.split_at(span.fragment.find('.').unwrap());
+    let fraction = &fraction[1..];
+
+    let decimal = Decimal {
+        digits: format!("{}{}", whole, fraction)
+            .parse()
+            .map_err(|_| out_of_range(span, Kind::Decimal))?,
+        places: fraction.len() as u32,
+    };
+
+    Ok((input, Expression::new(decimal.into(), Some(span))))
+}
+
+// Words are unsigned, so literals must fit in 64 bits.
+fn word(input: Span) -> IResult<Span, Expression> {
+    let (input, span) = digit1(input)?;
+    let word = span
+        .fragment
+        .parse::<u64>()
+        .map_err(|_| out_of_range(span, Kind::Word))?;
+
+    Ok((input, Expression::new(word.into(), Some(span))))
+}
+
+fn out_of_range(literal: Span, kind: Kind) -> Err<Error> {
+    Err::Failure(Error::from_sprint_error(
+        SprintError::OutOfRangeError(literal.fragment, kind),
+        Some(literal),
+    ))
+}
+
+const KEYWORDS: [&str; 8] = [
+    "fx", "import", "in", "let", "module", "obs", "roles", "where",
+];
+
 pub fn identifier(input: Span) -> IResult<Span, Span> {
     let lowercase1 = take_while1(|c: char| c.is_ascii_lowercase());
-    let (input, identifier) = recognize(pair(lowercase1, alphanumeric0))(input)?;
+    let (input, identifier) = verify(recognize(pair(lowercase1, alphanumeric0)), |s: &Span| {
+        !KEYWORDS.contains(&s.fragment)
+    })(input)?;
 
     Ok((input, identifier))
 }
 
-fn separator(input: Span) -> IResult<Span, Span> {
-    alt((space1, peek(tag("("))))(input)
+// Parses the contents of a non-empty string literal, which cannot span lines.
+fn string(input: Span) -> IResult<Span, Span> {
+    delimited(tag("\""), take_while1(|c| c != '"' && c != '\n'), tag("\""))(input)
+}
+
+fn keyword<'a>(keyword: &'static str) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    terminated(tag(keyword), not(alphanumeric1))
+}
+
+fn separator<'a>(layout: usize) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    let continuation = newline(move |column| column > layout);
+    alt((
+        recognize(many1(alt((space1, comment, continuation)))),
+        peek(tag("(")),
+    ))
+}
+
+// New lines, some of which may be blank or contain only comments, followed by indentation which
+// ends at a column satisfying the predicate.
+fn newline<'a>(
+    indentation: impl Fn(usize) -> bool,
+) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    let newlines = recognize(pair(many1(pair(blank0, line_ending)), space0));
+
+    verify(newlines, move |newline: &Span| {
+        let fragment = newline.fragment;
+        indentation(fragment.len() - fragment.rfind('\n').unwrap())
+    })
+}
+
+// Whitespace and comments within a line, although block comments may span several.
+fn blank0(input: Span) -> IResult<Span, Span> {
+    recognize(many0(alt((space1, comment))))(input)
+}
+
+fn operator<'a>(symbol: &'static str) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    // Operators must not be followed by another symbol, e.g. `-` should not match `->`.
+    terminated(tag(symbol), not(one_of("!#$%&*+./<=>?@\\^|-~:")))
 }
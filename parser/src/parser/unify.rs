@@ -21,35 +21,67 @@ impl<'a, T, U> Unify<'a, Context<'a, U>> for &mut Context<'a, T> {
             }
         }
 
-        for (variable, count) in other.variables {
-            if let Some(original) = self.variables.replace(variable.clone(), count) {
-                if let Err(e) = original.kind.unify(variable.kind.clone()) {
-                    let sprint_error =
-                        SprintError::TypeError(variable.name, e.sprint_error.unwrap().into());
-                    return Err(Error::from_sprint_error(sprint_error, variable.span));
+        for (name, signature) in other.signatures {
+            if let Some(original) = self.signatures.get(name) {
+                if let Err(e) = original
+                    .variable
+                    .kind
+                    .clone()
+                    .unify(signature.variable.kind)
+                {
+                    let sprint_error = SprintError::TypeError(name, e.sprint_error.unwrap().into());
+                    return Err(Error::from_sprint_error(
+                        sprint_error,
+                        signature.variable.span,
+                    ));
                 }
+            } else {
+                self.signatures.insert(name, signature);
             }
         }
 
+        // The kinds of variables are only unified once they are bound, by either an argument or a
+        // definition, as definitions can be used at different kinds.
+        for (variable, count) in other.variables {
+            self.variables.replace(variable, count);
+        }
+
         Ok(())
     }
 }
 
 impl<'a> Unify<'a> for Rc<Kind> {
     fn unify(self, other: Self) -> Result<(), Error<'a>> {
-        let mut this = Kind::simplify(self);
-        let mut other = Kind::simplify(other);
+        let this = Kind::simplify(self);
+        let other = Kind::simplify(other);
 
         match (this.as_ref(), other.as_ref()) {
             (Kind::Abstraction(this_from, this_to), Kind::Abstraction(other_from, other_to)) => {
                 this_from.clone().unify(other_from.clone())?;
                 this_to.clone().unify(other_to.clone())?;
             }
+            (Kind::Address, Kind::Address) => {}
+            (Kind::Amount(this_unit), Kind::Amount(other_unit))
+            | (Kind::Currency(this_unit), Kind::Currency(other_unit))
+            | (Kind::State(this_unit), Kind::State(other_unit)) => {
+                units(&[(this_unit, other_unit)], &this, &other)?;
+            }
             (Kind::Boolean, Kind::Boolean) => {}
+            (Kind::Role, Kind::Role) => {}
+            (Kind::Date, Kind::Date) => {}
+            (Kind::Decimal, Kind::Decimal) => {}
+            (Kind::Duration, Kind::Duration) => {}
             (Kind::Observable(this_k), Kind::Observable(other_k)) => {
                 this_k.clone().unify(other_k.clone())?;
             }
-            (Kind::State, Kind::State) => {}
+            (Kind::Rate(this_from, this_to), Kind::Rate(other_from, other_to)) => {
+                units(
+                    &[(this_from, other_from), (this_to, other_to)],
+                    &this,
+                    &other,
+                )?;
+            }
+            (Kind::Unit(this_name), Kind::Unit(other_name)) if this_name == other_name => {}
             (Kind::Unresolved(_), Kind::Unresolved(_)) if Rc::ptr_eq(&this, &other) => {}
             (Kind::Unresolved(k), _) if !Kind::contains(other.clone(), this.clone()) => {
                 *k.borrow_mut() = Some(other);
@@ -57,16 +89,33 @@ impl<'a> Unify<'a> for Rc<Kind> {
             (_, Kind::Unresolved(_)) => other.unify(this)?,
             (Kind::Word, Kind::Word) => {}
             _ => {
-                return Err(Error::from_sprint_error(
-                    SprintError::MismatchedKinds(
-                        Rc::make_mut(&mut this).clone(),
-                        Rc::make_mut(&mut other).clone(),
-                    ),
-                    None,
-                ))
+                return Err(mismatched(&this, &other));
             }
         }
 
         Ok(())
     }
 }
+
+// Units are unified within the kinds they are of, which are reported if they differ, as a unit
+// alone would not say whether it is of an amount or a contract.
+fn units<'a>(
+    units: &[(&Rc<Kind>, &Rc<Kind>)],
+    this: &Rc<Kind>,
+    other: &Rc<Kind>,
+) -> Result<(), Error<'a>> {
+    for (this_unit, other_unit) in units {
+        if (*this_unit).clone().unify((*other_unit).clone()).is_err() {
+            return Err(mismatched(this, other));
+        }
+    }
+
+    Ok(())
+}
+
+fn mismatched<'a>(this: &Rc<Kind>, other: &Rc<Kind>) -> Error<'a> {
+    Error::from_sprint_error(
+        SprintError::MismatchedKinds(this.as_ref().clone(), other.as_ref().clone()),
+        None,
+    )
+}
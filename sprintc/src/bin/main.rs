@@ -21,6 +21,14 @@ pub struct Args {
     /// Checks program without code generation
     #[structopt(short, long)]
     pub check: bool,
+
+    /// Directories searched for imported modules
+    #[structopt(short = "I", long = "include", parse(from_os_str))]
+    pub search_paths: Vec<PathBuf>,
+
+    /// Configuration of the oracles providing observables and of decimals, in TOML
+    #[structopt(long = "config", parse(from_os_str))]
+    pub config_path: Option<PathBuf>,
 }
 
 pub fn main() -> Result<(), Box<dyn Error>> {
@@ -31,6 +39,8 @@ pub fn main() -> Result<(), Box<dyn Error>> {
         output_path: args.output_path,
         verbose: args.verbose,
         check: args.check,
+        search_paths: args.search_paths,
+        config_path: args.config_path,
     };
 
     match compile(&args) {
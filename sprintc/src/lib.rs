@@ -1,11 +1,17 @@
-use sprint_move::generate;
-use sprint_parser::parser;
+use serde::Deserialize;
+use sprint_move::{generate, FixedPoint, Oracle, Oracles};
+use sprint_parser::{
+    ast::{Date, Definition, ExpressionType},
+    parser::{self, Arena, Module},
+};
 use std::{
     borrow::Cow,
+    collections::{HashMap, HashSet},
     error::Error,
     ffi::OsStr,
     fs::File,
     io::{BufReader, Read, Write},
+    iter,
     path::{Path, PathBuf},
 };
 
@@ -21,17 +27,53 @@ pub struct CompileArgs {
     pub verbose: bool,
     /// Checks program without code generation
     pub check: bool,
+    /// Directories searched for imported modules, after that of the importing file
+    pub search_paths: Vec<PathBuf>,
+    /// Optional path to a configuration of the oracles providing observables, and of decimals
+    pub config_path: Option<PathBuf>,
+}
+
+/// The configuration of the compiler, read from a TOML file such as
+///
+/// ```toml
+/// [oracles.Date]
+/// module = "0x1f.Date"
+/// address = "0x1f"
+///
+/// [oracles."USD/EUR"]
+/// module = "0x1f.ExchangeRate"
+/// address = "0x2a"
+///
+/// [decimal]
+/// precision = 4
+/// rounding = "nearest"
+/// ```
+#[derive(Deserialize, Default)]
+struct Config {
+    #[serde(default)]
+    oracles: HashMap<String, OracleConfig>,
+    #[serde(default)]
+    decimal: DecimalConfig,
+}
+
+#[derive(Deserialize)]
+struct OracleConfig {
+    module: String,
+    address: String,
+}
+
+#[derive(Deserialize, Default)]
+struct DecimalConfig {
+    precision: Option<u32>,
+    rounding: Option<String>,
 }
 
 pub fn compile<'a>(args: &'a CompileArgs) -> Result<Cow<'a, Path>, Box<dyn Error>> {
     let (source_path, output_path) = check_args(args)?;
+    let config = read_config(args.config_path.as_deref())?;
 
-    let source = read_source(source_path)?;
-
-    let ast = parser::contract(&source).map_err(|err| {
-        eprint!("{}", err.pretty(&source));
-        format!("Unable to parse file `{}`", source_path.display())
-    })?;
+    let arena = Arena::new();
+    let ast = read_program(source_path, &args.search_paths, &arena)?;
 
     if args.verbose {
         for definition in &ast {
@@ -42,13 +84,91 @@ pub fn compile<'a>(args: &'a CompileArgs) -> Result<Cow<'a, Path>, Box<dyn Error
     }
 
     if !args.check {
-        let output = generate(&ast);
+        let output = generate(&ast, &config)?;
         write_output(&output_path, output.as_bytes())?;
     }
 
     Ok(output_path)
 }
 
+/// The currencies paid by a program, in the order of the balances of a contract they are
+/// deposited to.
+pub fn currencies(
+    source_path: &Path,
+    search_paths: &[PathBuf],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let arena = Arena::new();
+    let ast = read_program(source_path, search_paths, &arena)?;
+
+    Ok(sprint_move::currencies(&ast)
+        .into_iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// The words the arguments of `main` are given to a contract as, from their literals, such as
+/// `1.5` for a decimal at the configured precision or `2020-01-01T00:00:00Z` for a date, apart
+/// from booleans which are given as themselves.
+pub fn arguments(
+    arguments: &[&str],
+    config_path: Option<&Path>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let fixed_point = read_config(config_path)?.fixed_point;
+
+    arguments
+        .iter()
+        .map(|argument| {
+            let literal = parser::literal(argument).map_err(|_| {
+                format!(
+                    "Invalid argument `{}`, expected a word, decimal, date, duration or boolean",
+                    argument
+                )
+            })?;
+
+            let word = match literal.expression {
+                ExpressionType::Boolean(boolean) => return Ok(boolean.to_string()),
+                ExpressionType::Date(Date::Date(seconds)) => Some(seconds),
+                ExpressionType::Decimal(decimal) => fixed_point.literal(&decimal),
+                ExpressionType::Duration(duration) => duration.word(),
+                ExpressionType::Word(word) => Some(word),
+                _ => None,
+            };
+
+            word.map(|word| word.to_string())
+                .ok_or_else(|| format!("Argument `{}` is out of range", argument).into())
+        })
+        .collect()
+}
+
+// Reads and checks a program, printing any error in the source of the module it is found in.
+fn read_program<'a>(
+    source_path: &Path,
+    search_paths: &[PathBuf],
+    arena: &'a Arena,
+) -> Result<Vec<Definition<'a>>, String> {
+    let (sources, modules): (Vec<_>, Vec<_>) = read_sources(source_path, search_paths, arena)?
+        .into_iter()
+        .map(|(path, source, module)| ((path, source), module))
+        .unzip();
+
+    parser::program(modules, arena).map_err(|err| {
+        // Errors refer to the source of the module they are found in.
+        let (path, source) = err
+            .nom_error
+            .as_ref()
+            .and_then(|nom_error| {
+                sources.iter().find(|(_, source)| {
+                    let range = source.as_ptr() as usize..=source.as_ptr() as usize + source.len();
+                    range.contains(&(nom_error.input.as_ptr() as usize))
+                })
+            })
+            .unwrap_or(&sources[0]);
+
+        eprint!("{}", err.pretty(source));
+        format!("Unable to parse file `{}`", path.display())
+    })
+}
+
 // Checks for presence of output path and that file extensions are valid.
 fn check_args(args: &CompileArgs) -> Result<(&Path, Cow<Path>), String> {
     let source = &args.source_path;
@@ -103,6 +223,112 @@ fn create_output_path(args: &CompileArgs) -> Result<Cow<Path>, String> {
     }
 }
 
+// Reads and parses the source of the program, followed by those of the modules it imports, which
+// are found relative to the importing file or else in one of the search paths.
+fn read_sources<'a>(
+    path: &Path,
+    search_paths: &[PathBuf],
+    arena: &'a Arena,
+) -> Result<Vec<(PathBuf, &'a str, Module<'a>)>, String> {
+    let mut sources = vec![read_module(path, None, arena)?];
+    let mut modules = HashSet::new();
+    let mut i = 0;
+
+    while i < sources.len() {
+        let (path, _, module) = &sources[i];
+        let path = path.clone();
+
+        let imports: Vec<_> = module
+            .imports
+            .iter()
+            .map(|import| import.module.fragment)
+            .filter(|name| modules.insert(*name))
+            .collect();
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        for name in imports {
+            let mut module_path: PathBuf = name.split('.').collect();
+            module_path.set_extension(SPRINT_EXTENSION);
+
+            let import_path = iter::once(directory)
+                .chain(search_paths.iter().map(PathBuf::as_path))
+                .map(|directory| directory.join(&module_path))
+                .find(|path| path.is_file())
+                .ok_or_else(|| {
+                    format!(
+                        "Unable to find module `{}` imported by `{}`",
+                        name,
+                        path.display()
+                    )
+                })?;
+
+            sources.push(read_module(&import_path, Some(name), arena)?);
+        }
+
+        i += 1;
+    }
+
+    Ok(sources)
+}
+
+// Parses a module, whose name must match the path it was imported by.
+fn read_module<'a>(
+    path: &Path,
+    name: Option<&str>,
+    arena: &'a Arena,
+) -> Result<(PathBuf, &'a str, Module<'a>), String> {
+    let source: &str = arena.alloc_str(&read_source(path)?);
+
+    let module = parser::module(source).map_err(|err| {
+        eprint!("{}", err.pretty(source));
+        format!("Unable to parse file `{}`", path.display())
+    })?;
+
+    if let Some(name) = name {
+        if module.name.map(|name| name.fragment) != Some(name) {
+            return Err(format!(
+                "Expected file `{}` to define module `{}`",
+                path.display(),
+                name
+            ));
+        }
+    }
+
+    Ok((path.to_path_buf(), source, module))
+}
+
+// Decimals have the default fixed point unless configured, while each observable used, including
+// the date, must have a configured oracle.
+fn read_config(path: Option<&Path>) -> Result<sprint_move::Config, String> {
+    let config: Config = match path {
+        Some(path) => toml::from_str(&read_source(path)?)
+            .map_err(|err| format!("Unable to read configuration `{}`: {}", path.display(), err))?,
+        None => Config::default(),
+    };
+
+    let oracles: Oracles = config
+        .oracles
+        .into_iter()
+        .map(|(name, oracle)| (name, Oracle::new(oracle.module, oracle.address)))
+        .collect();
+
+    let mut fixed_point = FixedPoint::default();
+
+    if let Some(precision) = config.decimal.precision {
+        fixed_point.precision = precision;
+    }
+
+    if let Some(rounding) = config.decimal.rounding {
+        fixed_point.rounding = rounding.parse()?;
+    }
+
+    Ok(sprint_move::Config {
+        oracles,
+        fixed_point,
+    })
+}
+
 fn read_source(path: &Path) -> Result<String, String> {
     let source_file = File::open(path)
         .map_err(|err| format!("Unable to open file `{}`: {}", path.display(), err))?;
@@ -131,6 +357,8 @@ fn write_output(path: &Path, buf: &[u8]) -> Result<(), String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sprint_parser::ast::Duration;
+    use std::{env, fs};
 
     #[test]
     fn create_output_path_no_output_specified() {
@@ -139,6 +367,8 @@ mod tests {
             output_path: None,
             verbose: false,
             check: false,
+            search_paths: Vec::new(),
+            config_path: None,
         };
 
         assert_eq!(
@@ -154,6 +384,8 @@ mod tests {
             output_path: Some(PathBuf::from("output.mvir")),
             verbose: false,
             check: false,
+            search_paths: Vec::new(),
+            config_path: None,
         };
 
         assert_eq!(
@@ -161,4 +393,143 @@ mod tests {
             PathBuf::from("output.mvir")
         );
     }
+
+    // Writes the sources of a program importing modules from its directory and a search path.
+    fn write_sources(name: &str) -> (PathBuf, PathBuf) {
+        let directory = env::temp_dir().join(name);
+        let include = directory.join("include");
+        fs::create_dir_all(include.join("Finance")).unwrap();
+
+        let sources = [
+            (
+                directory.join("main.sprint"),
+                "import Finance.Bonds\nimport Options\nmain = and european zcb",
+            ),
+            (
+                directory.join("Options.sprint"),
+                "module Options where\nimport Finance.Bonds\neuropean = give zcb",
+            ),
+            (
+                include.join("Finance/Bonds.sprint"),
+                "module Finance.Bonds where\nzcb = one \"USD\"",
+            ),
+            (
+                directory.join("misnamed.sprint"),
+                "import Bonds\nmain = zero",
+            ),
+            (
+                directory.join("Bonds.sprint"),
+                "module Finance.Bonds where\nzcb = one \"USD\"",
+            ),
+        ];
+
+        for (path, source) in &sources {
+            fs::write(path, source).unwrap();
+        }
+
+        (directory, include)
+    }
+
+    #[test]
+    fn read_sources_imported() {
+        let (directory, include) = write_sources("sprintc_read_sources_imported");
+        let arena = Arena::new();
+
+        let sources =
+            read_sources(&directory.join("main.sprint"), &[include.clone()], &arena).unwrap();
+        let paths: Vec<_> = sources.iter().map(|(path, _, _)| path.clone()).collect();
+
+        assert_eq!(
+            paths,
+            [
+                directory.join("main.sprint"),
+                include.join("Finance/Bonds.sprint"),
+                directory.join("Options.sprint")
+            ]
+        );
+        assert_eq!(sources[1].2.name.unwrap().fragment, "Finance.Bonds");
+    }
+
+    #[test]
+    fn read_sources_invalid() {
+        let (directory, _) = write_sources("sprintc_read_sources_invalid");
+        let arena = Arena::new();
+
+        assert_eq!(
+            read_sources(&directory.join("main.sprint"), &[], &arena).unwrap_err(),
+            format!(
+                "Unable to find module `Finance.Bonds` imported by `{}`",
+                directory.join("main.sprint").display()
+            )
+        );
+        assert_eq!(
+            read_sources(&directory.join("misnamed.sprint"), &[], &arena).unwrap_err(),
+            format!(
+                "Expected file `{}` to define module `Bonds`",
+                directory.join("Bonds.sprint").display()
+            )
+        );
+    }
+
+    #[test]
+    fn read_config_date() {
+        let arena = Arena::new();
+        let definitions =
+            parser::contract("main = after 2020-01-01T00:00:00Z (one \"USD\")", &arena).unwrap();
+
+        assert_eq!(
+            generate(&definitions, &read_config(None).unwrap()).unwrap_err(),
+            "No oracle is configured for the observable \"Date\""
+        );
+
+        let path = env::temp_dir().join("sprintc_read_config_date.toml");
+        fs::write(
+            &path,
+            "[oracles.Date]\nmodule = \"0x1f.Date\"\naddress = \"0x1f\"",
+        )
+        .unwrap();
+
+        assert!(generate(&definitions, &read_config(Some(&path)).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn arguments_as_words() {
+        assert_eq!(
+            arguments(&["3", "1.5", "2020-01-01T00:00:00Z", "30d", "True"], None).unwrap(),
+            ["3", "1500000", "1577836800", "2592000", "true"]
+        );
+        assert_eq!(
+            arguments(&["1m"], None).unwrap(),
+            [Duration {
+                months: 1,
+                seconds: 0
+            }
+            .word()
+            .unwrap()
+            .to_string()]
+        );
+
+        let path = env::temp_dir().join("sprintc_arguments_as_words.toml");
+        fs::write(&path, "[decimal]\nprecision = 2").unwrap();
+
+        assert_eq!(arguments(&["1.5"], Some(&path)).unwrap(), ["150"]);
+        assert_eq!(
+            arguments(&["\"USD\""], None).unwrap_err().to_string(),
+            "Invalid argument `\"USD\"`, expected a word, decimal, date, duration or boolean"
+        );
+        assert_eq!(
+            arguments(&["18446744073709551.615"], None)
+                .unwrap_err()
+                .to_string(),
+            "Argument `18446744073709551.615` is out of range"
+        );
+    }
+
+    #[test]
+    fn currencies_by_name() {
+        let path = env::temp_dir().join("sprintc_currencies_by_name.sprint");
+        fs::write(&path, "main = and (one \"USD\") (give (one \"EUR\"))").unwrap();
+
+        assert_eq!(currencies(&path, &[]).unwrap(), ["EUR", "USD"]);
+    }
 }